@@ -8,6 +8,13 @@
 //!
 //! Backend features:
 //! * [redis]: Enables the Redis backend. (Default)
+//!
+//! Other features:
+//! * async: Enables async consumers like [List::into_pop_stream](redis::List::into_pop_stream) based on tokio.
+//! * compression: Enables gzip and zstd compression of stored values.
+//! * messagepack: Enables [Generic::with_messagepack](redis::Generic::with_messagepack) to store values as MessagePack for services in other languages.
+//! * sentinel: Enables [Connector](redis::Connector) to resolve the master through Redis Sentinel.
+//! * tracing: Wraps every Redis operation in a [tracing](https://docs.rs/tracing) span.
 
 /// This module contains the types that can be used with a Redis backend. Must be enabled by feature `redis`.
 #[cfg(feature = "redis")]
@@ -0,0 +1,131 @@
+//! # Backoff
+//! This module contains the backoff between two attempts of the blocking operations.
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+/// An exponential backoff with jitter, which is used between two attempts to acquire a lock
+/// or to store a value, so a waiting instance does not occupy a CPU core and Redis under contention.
+///
+/// The first attempt is done immediately. Afterward, the backoff starts at the minimum and is doubled
+/// after every failed attempt up to the maximum. With jitter, a random duration between
+/// the half and the full backoff is slept, so competing instances do not retry at the same time.
+///
+/// The default backoff is from 1ms up to 64ms with jitter.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::{Backoff, Di32, Mutex};
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let backoff = Backoff::new(Duration::from_millis(5), Duration::from_millis(100));
+/// let mut lock = Mutex::new(Di32::new("test_backoff_example", client)).with_backoff(backoff);
+/// let mut guard = lock.lock().unwrap();
+/// guard.store(1).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    jitter: bool,
+}
+
+impl Backoff {
+    /// Creates a new backoff from min up to max with jitter.
+    /// If max is lower than min, min is used as maximum.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+            jitter: true,
+        }
+    }
+
+    /// Disables the jitter, so the backoff is slept as it is.
+    pub fn without_jitter(mut self) -> Self {
+        self.jitter = false;
+        self
+    }
+
+    /// Starts a new sequence of attempts.
+    pub(crate) fn start(&self) -> BackoffState {
+        BackoffState {
+            backoff: *self,
+            current: self.min,
+        }
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(1), Duration::from_millis(64))
+    }
+}
+
+/// The state of a sequence of attempts, which is started by [Backoff::start].
+pub(crate) struct BackoffState {
+    backoff: Backoff,
+    current: Duration,
+}
+
+impl BackoffState {
+    /// Returns the duration to sleep before the next attempt and increases the backoff.
+    pub(crate) fn next_delay(&mut self) -> Duration {
+        let delay = if self.backoff.jitter {
+            let half = self.current / 2;
+            half + random_below(self.current - half)
+        } else {
+            self.current
+        };
+        self.current = (self.current * 2).min(self.backoff.max);
+        delay
+    }
+
+    /// Sleeps before the next attempt.
+    pub(crate) fn wait(&mut self) {
+        std::thread::sleep(self.next_delay());
+    }
+
+    /// Sleeps before the next attempt, but not beyond the deadline.
+    /// Returns false without sleeping, if the deadline is already reached.
+    pub(crate) fn wait_until(&mut self, deadline: Instant) -> bool {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        std::thread::sleep(self.next_delay().min(remaining));
+        true
+    }
+}
+
+/// Returns a random duration between zero and the bound (inclusive).
+/// The randomness is taken from the keys of the std hasher, so no additional dependency is needed.
+fn random_below(bound: Duration) -> Duration {
+    let nanos = bound.as_nanos() as u64;
+    if nanos == 0 {
+        return bound;
+    }
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_nanos(random % (nanos + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let backoff = Backoff::new(Duration::from_millis(1), Duration::from_millis(4));
+        let mut state = backoff.without_jitter().start();
+        let delays: Vec<_> = (0..4).map(|_| state.next_delay().as_millis()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 4]);
+
+        let mut state = backoff.start();
+        for max in [1, 2, 4, 4] {
+            let delay = state.next_delay();
+            assert!(delay >= Duration::from_millis(max) / 2);
+            assert!(delay <= Duration::from_millis(max));
+        }
+    }
+}
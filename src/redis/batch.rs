@@ -0,0 +1,112 @@
+//! # Batch
+//! This module contains the batch type, which chains arithmetic locally and stores the result once.
+use crate::redis::Generic;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ops;
+
+/// A batch of arithmetic operations on a [Generic].
+///
+/// The operators of [Generic] store the value after every operation, so a chain like `a + b + c`
+/// needs one round trip per operator. The batch computes the operations locally starting
+/// from the cached value and stores the result with a single SET on [Batch::commit].
+///
+/// Like the operators, the first operation on an empty cache uses the right hand side as value.
+/// If the batch is dropped without a commit, nothing is stored.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::Di32 as i32;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut i32 = i32::with_value(1, "test_batch_example", client);
+/// i32.batch().add(2).sub(1).mul(5).commit();
+/// assert_eq!(i32, 10);
+/// ```
+pub struct Batch<'a, T> {
+    data: &'a mut Generic<T>,
+    value: Option<T>,
+}
+
+// the methods are named like the operators on purpose, but they only compute locally
+#[allow(clippy::should_implement_trait)]
+impl<'a, T> Batch<'a, T>
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    pub(crate) fn new(data: &'a mut Generic<T>) -> Self {
+        let value = data.cache.clone();
+        Self { data, value }
+    }
+
+    fn apply(mut self, rhs: T, func: impl Fn(T, T) -> T) -> Self {
+        self.value = Some(match self.value.take() {
+            Some(value) => func(value, rhs),
+            None => rhs,
+        });
+        self
+    }
+
+    /// Adds the value locally.
+    pub fn add(self, rhs: T) -> Self
+    where
+        T: ops::Add<Output = T>,
+    {
+        self.apply(rhs, |a, b| a + b)
+    }
+
+    /// Subtracts the value locally.
+    pub fn sub(self, rhs: T) -> Self
+    where
+        T: ops::Sub<Output = T>,
+    {
+        self.apply(rhs, |a, b| a - b)
+    }
+
+    /// Multiplies the value locally.
+    pub fn mul(self, rhs: T) -> Self
+    where
+        T: ops::Mul<Output = T>,
+    {
+        self.apply(rhs, |a, b| a * b)
+    }
+
+    /// Divides the value locally.
+    pub fn div(self, rhs: T) -> Self
+    where
+        T: ops::Div<Output = T>,
+    {
+        self.apply(rhs, |a, b| a / b)
+    }
+
+    /// Returns the locally computed value, which will be stored on commit.
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Stores the computed value with a single round trip.
+    /// If no operation was applied on an empty cache, nothing is stored.
+    pub fn commit(self) {
+        if let Some(value) = self.value {
+            self.data.store(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::redis::Di32;
+
+    #[test]
+    fn test_batch() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut i32 = Di32::new("test_batch", client.clone());
+        let batch = i32.batch().add(4).div(2);
+        assert_eq!(batch.value(), Some(&2));
+        batch.commit();
+        assert_eq!(Di32::with_load("test_batch", client), 2);
+
+        i32.batch().mul(3);
+        assert_eq!(i32, 2);
+    }
+}
@@ -0,0 +1,131 @@
+//! # BitMap
+//! This module contains the bitmap type.
+use crate::redis::{instrumented, Connector};
+use redis::RedisResult;
+
+/// A bitmap that is stored in Redis.
+///
+/// It uses one bit per offset, so it is a compact way to track presence,
+/// e.g. which of millions of user ids are active.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::BitMap;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut active = BitMap::new("test_bitmap_example", client.clone());
+/// let mut other = BitMap::new("test_bitmap_example2", client);
+/// active.clear();
+/// other.clear();
+/// active.set_bit(7, true);
+/// other.set_bit(1_000, true);
+/// assert!(active.get_bit(7));
+/// assert!(!active.get_bit(8));
+/// active.or_with(&[&other]);
+/// assert_eq!(active.count(), 2);
+/// ```
+pub struct BitMap {
+    key: String,
+    client: Connector,
+}
+
+impl BitMap {
+    /// Creates a new BitMap.
+    /// Already stored bits in redis are kept.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+        }
+    }
+
+    /// Sets the bit at the offset and returns its previous value.
+    pub fn set_bit(&mut self, offset: u64, value: bool) -> bool {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: RedisResult<bool> = instrumented("set_bit", &self.key, || {
+            redis::cmd("SETBIT")
+                .arg(&self.key)
+                .arg(offset)
+                .arg(value as u8)
+                .query(&mut conn)
+        });
+        res.expect("Failed to set bit")
+    }
+
+    /// Returns the bit at the offset. Bits, which were never set, are false.
+    /// If a replica is configured in the [Connector], it is read from the replica.
+    pub fn get_bit(&self, offset: u64) -> bool {
+        let mut conn = self.client.get_read_connection().unwrap();
+        let res: RedisResult<bool> = instrumented("get_bit", &self.key, || {
+            redis::cmd("GETBIT")
+                .arg(&self.key)
+                .arg(offset)
+                .query(&mut conn)
+        });
+        res.expect("Failed to get bit")
+    }
+
+    /// Returns the number of set bits with BITCOUNT.
+    /// If a replica is configured in the [Connector], it is read from the replica.
+    pub fn count(&self) -> u64 {
+        let mut conn = self.client.get_read_connection().unwrap();
+        let res: RedisResult<u64> = instrumented("count", &self.key, || {
+            redis::cmd("BITCOUNT").arg(&self.key).query(&mut conn)
+        });
+        res.expect("Failed to count bits")
+    }
+
+    /// Combines this bitmap with the others by a bitwise OR and stores the result in this bitmap.
+    pub fn or_with(&mut self, others: &[&BitMap]) {
+        self.bit_op("OR", others);
+    }
+
+    /// Combines this bitmap with the others by a bitwise AND and stores the result in this bitmap.
+    pub fn and_with(&mut self, others: &[&BitMap]) {
+        self.bit_op("AND", others);
+    }
+
+    fn bit_op(&mut self, op: &str, others: &[&BitMap]) {
+        let mut conn = self.client.get_connection().unwrap();
+        let mut cmd = redis::cmd("BITOP");
+        cmd.arg(op).arg(&self.key).arg(&self.key);
+        for other in others {
+            cmd.arg(&other.key);
+        }
+        instrumented("bit_op", &self.key, || cmd.query::<()>(&mut conn))
+            .expect("Failed to combine bitmaps");
+    }
+
+    /// Removes all bits
+    pub fn clear(&self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("clear", &self.key, || {
+            redis::Cmd::del(&self.key).query::<()>(&mut conn)
+        })
+        .expect("Failed to clear bitmap");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitmap() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut bitmap = BitMap::new("test_bitmap", client.clone());
+        let mut other = BitMap::new("test_bitmap2", client);
+        bitmap.clear();
+        other.clear();
+
+        assert!(!bitmap.set_bit(1, true));
+        assert!(bitmap.set_bit(1, true));
+        bitmap.set_bit(2, true);
+        other.set_bit(2, true);
+        assert_eq!(bitmap.count(), 2);
+        bitmap.and_with(&[&other]);
+        assert!(!bitmap.get_bit(1));
+        assert!(bitmap.get_bit(2));
+        assert_eq!(bitmap.count(), 1);
+    }
+}
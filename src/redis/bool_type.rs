@@ -6,6 +6,7 @@ pub type TBool = crate::redis::Generic<bool>;
 mod tests {
     use super::*;
 
+    #[allow(clippy::bool_assert_comparison)]
     #[test]
     fn test_bool() {
         let client = redis::Client::open("redis://localhost/").unwrap();
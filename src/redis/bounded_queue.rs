@@ -0,0 +1,165 @@
+//! # Bounded Queue
+//! This module contains the queue type with a capacity.
+use crate::redis::compression::{decode, encode};
+use crate::redis::{instrumented, Backoff, Compression, Connector};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// The push script.
+/// It is used to add an item to the back of the queue, only if the queue is not full.
+/// The length is checked and the item is pushed in one script, so the capacity cannot be exceeded
+/// by concurrent producers.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the queue,
+/// 2. The capacity of the queue,
+/// 3. The value to push.
+const PUSH_SCRIPT: &str = r#"
+if redis.call("LLEN", ARGV[1]) < tonumber(ARGV[2]) then
+    redis.call("RPUSH", ARGV[1], ARGV[3])
+    return 1
+end
+return 0"#;
+
+/// A FIFO queue with a capacity, which is stored as list in Redis.
+///
+/// [BoundedQueue::push] blocks, while the queue is full, until a consumer pops an item.
+/// So a fast producer gets back-pressure and cannot fill up the memory of Redis.
+/// The length of the list is used as the number of taken permits, so no separate counter can get out of sync.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::BoundedQueue;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut queue = BoundedQueue::new("test_bounded_queue_example", 2, client);
+/// queue.clear();
+/// queue.push(&1);
+/// assert!(queue.try_push(&2));
+/// assert!(!queue.try_push(&3));
+/// assert_eq!(queue.pop(), Some(1));
+/// assert!(queue.try_push(&3));
+/// ```
+pub struct BoundedQueue<T> {
+    key: String,
+    capacity: usize,
+    client: Connector,
+    backoff: Backoff,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> BoundedQueue<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new BoundedQueue with the given capacity.
+    /// Already stored items in redis are kept, even if there are more than the capacity.
+    pub fn new(key: &str, capacity: usize, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            capacity,
+            client: client.into(),
+            backoff: Backoff::default(),
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Sets the backoff between two attempts to push to a full queue.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Returns the capacity of the queue.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Adds the item to the back of the queue.
+    /// This function blocks, while the queue is full.
+    pub fn push(&mut self, item: &T) {
+        let mut backoff = self.backoff.start();
+        while !self.try_push(item) {
+            backoff.wait();
+        }
+    }
+
+    /// Adds the item to the back of the queue without waiting.
+    /// Returns false, if the queue is full.
+    pub fn try_push(&mut self, item: &T) -> bool {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("try_push", &self.key, || {
+            redis::Script::new(PUSH_SCRIPT)
+                .arg(&self.key)
+                .arg(self.capacity)
+                .arg(encode(item, Compression::Raw))
+                .invoke(&mut conn)
+        })
+        .expect("Failed to push value")
+    }
+
+    /// Removes and returns the oldest item, which frees a place for a waiting producer.
+    /// Returns None, if the queue is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: Option<Vec<u8>> = instrumented("pop", &self.key, || {
+            redis::Cmd::lpop(&self.key, None).query(&mut conn)
+        })
+        .expect("Failed to pop value");
+        res.map(|v| decode(&v))
+    }
+
+    /// Returns the number of items in the queue.
+    pub fn len(&self) -> usize {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("len", &self.key, || {
+            redis::Cmd::llen(&self.key).query(&mut conn)
+        })
+        .unwrap()
+    }
+
+    /// Returns true if the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all items from the queue
+    pub fn clear(&self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("clear", &self.key, || {
+            redis::Cmd::del(&self.key).query::<()>(&mut conn)
+        })
+        .expect("Failed to clear queue");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_bounded_queue() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut queue = BoundedQueue::new("test_bounded_queue", 1, client.clone());
+        queue.clear();
+        queue.push(&1);
+        assert!(!queue.try_push(&2));
+
+        let elapsed = thread::scope(|s| {
+            s.spawn(|| {
+                let mut consumer = BoundedQueue::<i32>::new("test_bounded_queue", 1, client);
+                thread::sleep(Duration::from_millis(200));
+                assert_eq!(consumer.pop(), Some(1));
+            });
+            // the producer waits, until the consumer popped the first item
+            let start = Instant::now();
+            queue.push(&2);
+            start.elapsed()
+        });
+        assert!(elapsed >= Duration::from_millis(150), "{:?}", elapsed);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop(), Some(2));
+    }
+}
@@ -0,0 +1,172 @@
+//! # Channel
+//! This module contains the channel type.
+use crate::redis::compression::{decode, encode};
+use crate::redis::{instrumented, Compression, Connector};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+/// A multi-producer, multi-consumer channel, which is stored as list in Redis.
+///
+/// It is the distributed analogue of [std::sync::mpsc]. Messages are sent with RPUSH and received with
+/// BLPOP, so they are received in FIFO order and every message is delivered to exactly one consumer.
+/// Multiple instances can send and receive on the same key.
+///
+/// In contrast to pub/sub, messages are kept in Redis until they are received,
+/// so no message is lost, if there is no consumer at the moment.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::Channel;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut rx = Channel::new("test_channel_example", client.clone());
+/// rx.clear();
+///
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         let mut tx = Channel::new("test_channel_example", client.clone());
+///         tx.send(&"Hello".to_string());
+///     });
+///     assert_eq!(rx.recv(Duration::from_secs(1)), Some("Hello".to_string()));
+/// });
+/// assert_eq!(rx.try_recv(), None);
+/// ```
+pub struct Channel<T> {
+    key: String,
+    client: Connector,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> Channel<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new Channel.
+    /// Messages, which are already stored in Redis, are kept and can be received.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Sends the message to the channel.
+    /// It does not wait for a consumer.
+    pub fn send(&mut self, msg: &T) {
+        let mut conn = self.client.get_connection().unwrap();
+        let cmd = redis::Cmd::rpush(&self.key, encode(msg, Compression::Raw));
+        instrumented("send", &self.key, || cmd.query::<()>(&mut conn))
+            .expect("Failed to send message");
+    }
+
+    /// Receives the oldest message and waits up to the timeout for one with BLPOP.
+    /// Returns None, if no message was sent in time.
+    ///
+    /// Redis rounds the timeout to milliseconds. A zero timeout does not wait like [Channel::try_recv].
+    pub fn recv(&mut self, timeout: Duration) -> Option<T> {
+        if timeout.is_zero() {
+            return self.try_recv();
+        }
+
+        let mut conn = self.client.get_connection().unwrap();
+        // a zero timeout blocks forever in Redis, so the timeout is at least one millisecond
+        let secs = timeout.max(Duration::from_millis(1)).as_secs_f64();
+        let res: Option<(String, Vec<u8>)> = instrumented("recv", &self.key, || {
+            redis::cmd("BLPOP")
+                .arg(&self.key)
+                .arg(secs)
+                .query(&mut conn)
+        })
+        .expect("Failed to receive message");
+        res.map(|(_, v)| decode(&v))
+    }
+
+    /// Receives the oldest message without waiting.
+    /// Returns None, if there is no message.
+    pub fn try_recv(&mut self) -> Option<T> {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: Option<Vec<u8>> = instrumented("try_recv", &self.key, || {
+            redis::Cmd::lpop(&self.key, None).query(&mut conn)
+        })
+        .expect("Failed to receive message");
+        res.map(|v| decode(&v))
+    }
+
+    /// Returns the number of messages, which are not received yet.
+    pub fn len(&self) -> usize {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("len", &self.key, || {
+            redis::Cmd::llen(&self.key).query(&mut conn)
+        })
+        .unwrap()
+    }
+
+    /// Returns true if there is no message
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all messages from the channel
+    pub fn clear(&self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("clear", &self.key, || {
+            redis::Cmd::del(&self.key).query::<()>(&mut conn)
+        })
+        .expect("Failed to clear channel");
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    /// Creates a new handle to the same channel in Redis.
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            client: self.client.clone(),
+            _phantom: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_channel() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut tx = Channel::new("test_channel", client);
+        tx.clear();
+        assert_eq!(tx.recv(Duration::from_millis(100)), None);
+
+        let mut received: Vec<i32> = thread::scope(|s| {
+            let consumers: Vec<_> = (0..2)
+                .map(|_| {
+                    let mut rx = tx.clone();
+                    s.spawn(move || {
+                        let mut received = Vec::new();
+                        while let Some(v) = rx.recv(Duration::from_millis(500)) {
+                            received.push(v);
+                        }
+                        received
+                    })
+                })
+                .collect();
+            for i in 0..10 {
+                tx.send(&i);
+            }
+            consumers
+                .into_iter()
+                .flat_map(|c| c.join().unwrap())
+                .collect()
+        });
+        // every message is received exactly once
+        received.sort();
+        assert_eq!(received, (0..10).collect::<Vec<_>>());
+        assert!(tx.is_empty());
+    }
+}
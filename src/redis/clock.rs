@@ -1,28 +1,31 @@
-use crate::redis::Generic;
-use serde_json::from_str;
+use crate::redis::{instrumented, Backoff, Generic, DEFAULT_SEPARATOR};
 use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ClockOrderedError {
     #[error("Ordering number is not greater than current number stored in redis.")]
     OrderError,
+    #[error("Value could not be stored before the deadline.")]
+    Timeout,
 }
 
 /// This is the set_load script.
 /// It is used to set the value if order is greater than the current order.
 /// Returns the current value and the current_ordering number.
 ///
-/// It takes 3 arguments:
+/// It takes 4 arguments:
 /// 1. The key of value to set
 /// 2. The order_number of the setting operation
 /// 3. The value itself to set
+/// 4. The separator of the derived keys
 const SET_LOAD_SCRIPT: &str = r#"
 local key = ARGV[1]
 local order = ARGV[2]
-local current_order = redis.call("GET", key .. ":order")
+local current_order = redis.call("GET", key .. ARGV[4] .. "order")
 if current_order == false or current_order < order then
-    redis.call("SET", key .. ":order", order)
+    redis.call("SET", key .. ARGV[4] .. "order", order)
     redis.call("SET", key, ARGV[3])
     current_order = order
 end
@@ -33,11 +36,12 @@ return {redis.call("GET", key), current_order}
 /// It is used to load the value and the order number of the value.
 /// Returns the current value and the current ordering number.
 ///
-/// It takes 1 argument:
+/// It takes 2 arguments:
 /// 1. The key of value to load
+/// 2. The separator of the derived keys
 const LOAD_SCRIPT: &str = r#"
 local key = ARGV[1]
-return {redis.call("GET", key), redis.call("GET", key .. ":order")}
+return {redis.call("GET", key), redis.call("GET", key .. ARGV[2] .. "order")}
 "#;
 
 /// The ClockOrdered type.
@@ -51,10 +55,15 @@ return {redis.call("GET", key), redis.call("GET", key .. ":order")}
 /// Mostly used in situations, where your value changes rarely but read often.
 /// Another use case is, when it is okay for you, that the value could be not the latest or
 /// computing a derived value multiple times is acceptable.
+///
+/// The ordering number is stored in the derived key `<key>:order`.
+/// If `:` clashes with the naming convention of your keys, use [ClockOrdered::with_separator].
 #[derive(Debug)]
 pub struct ClockOrdered<T> {
     data: Generic<T>,
     counter: usize,
+    backoff: Backoff,
+    separator: String,
 }
 
 impl<T> ClockOrdered<T>
@@ -64,11 +73,44 @@ where
     /// Creates a new ClockOrdered.
     /// The value is loaded from redis directly.
     pub fn new(data: Generic<T>) -> Self {
-        let mut s = Self { data, counter: 0 };
+        let mut s = Self {
+            data,
+            counter: 0,
+            backoff: Backoff::default(),
+            separator: DEFAULT_SEPARATOR.to_string(),
+        };
         s.load();
         s
     }
 
+    /// Sets the separator between the key and the suffix of the order key, e.g. `<key>/order` for `/`.
+    /// The default is `:`. The value and the order are loaded again with the new separator.
+    ///
+    /// All instances, which store the same value, have to use the same separator.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Generic;
+    /// use dtypes::redis::ClockOrdered;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32: Generic<i32> = Generic::new("test_clock_ordered_separator_example", client);
+    /// let mut clock_ordered = ClockOrdered::new(i32).with_separator("/");
+    /// clock_ordered.store(1).unwrap();
+    /// assert_eq!(*clock_ordered, 1);
+    /// ```
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self.load();
+        self
+    }
+
+    /// Sets the backoff between two attempts of [ClockOrdered::store_blocking].
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
     /// Stores the value in the redis server.
     /// The value is only stored if the ordering_number is greater than the current number.
     /// The order is incremented by one before each store.
@@ -113,11 +155,11 @@ where
     /// ```
     pub fn store(&mut self, val: T) -> Result<(), ClockOrderedError> {
         self.counter += 1;
-        let val_json = serde_json::to_string(&val).unwrap();
-        let (v, order) = self.store_redis(&val_json);
+        let encoded = self.data.encode_value(&val);
+        let (v, order) = self.store_redis(&encoded);
 
         if let Some(v) = v {
-            if self.counter >= order && v == val_json {
+            if self.counter >= order && v == encoded {
                 self.data.cache = Some(val);
                 return Ok(());
             }
@@ -128,6 +170,10 @@ where
     /// Stores the value in the redis server and blocks until succeeds.
     /// Everything else is equal to [ClockOrdered::store].
     ///
+    /// Between two attempts it sleeps with the [Backoff] set by [ClockOrdered::with_backoff],
+    /// by default from 1ms up to 64ms, so it does not occupy a CPU core, while competing stores keep winning.
+    /// It has no deadline, so use [ClockOrdered::store_blocking_timeout] to bound the time.
+    ///
     /// # Example
     /// ```
     /// use std::thread;
@@ -152,26 +198,66 @@ where
     /// });
     /// ```
     pub fn store_blocking(&mut self, val: T) -> Result<(), ClockOrderedError> {
-        let val_json = serde_json::to_string(&val).unwrap();
-        let mut res = self.store_redis(&val_json);
+        self.store_until(val, None)
+    }
+
+    /// Stores the value in the redis server and blocks until succeeds like [ClockOrdered::store_blocking].
+    /// Returns [ClockOrderedError::Timeout], if the value could not be stored within the timeout.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use dtypes::redis::Generic;
+    /// use dtypes::redis::ClockOrdered;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32: Generic<i32> = Generic::new("test_add_clock_ordered_example4", client);
+    /// let mut clock_ordered = ClockOrdered::new(i32);
+    /// clock_ordered.store_blocking_timeout(2, Duration::from_secs(1)).unwrap();
+    /// assert_eq!(*clock_ordered, 2);
+    /// ```
+    pub fn store_blocking_timeout(
+        &mut self,
+        val: T,
+        timeout: Duration,
+    ) -> Result<(), ClockOrderedError> {
+        self.store_until(val, Some(Instant::now() + timeout))
+    }
+
+    fn store_until(&mut self, val: T, deadline: Option<Instant>) -> Result<(), ClockOrderedError> {
+        let encoded = self.data.encode_value(&val);
+        let mut res = self.store_redis(&encoded);
+        let mut backoff = self.backoff.start();
+
+        while self.counter < res.1 || res.0.as_deref() != Some(encoded.as_slice()) {
+            match deadline {
+                Some(deadline) => {
+                    if !backoff.wait_until(deadline) {
+                        return Err(ClockOrderedError::Timeout);
+                    }
+                }
+                None => backoff.wait(),
+            }
 
-        while self.counter < res.1 || res.0.is_none() || res.0.unwrap() != val_json {
             self.counter = res.1 + 1;
-            res = self.store_redis(&val_json);
+            res = self.store_redis(&encoded);
         }
 
         self.data.cache = Some(val);
         Ok(())
     }
 
-    fn store_redis(&self, val: &str) -> (Option<String>, usize) {
+    fn store_redis(&self, val: &[u8]) -> (Option<Vec<u8>>, usize) {
         let mut conn = self.data.client.get_connection().unwrap();
-        redis::Script::new(SET_LOAD_SCRIPT)
-            .arg(&self.data.key)
-            .arg(self.counter)
-            .arg(val)
-            .invoke(&mut conn)
-            .expect("Could not execute script")
+        instrumented("store", &self.data.key, || {
+            redis::Script::new(SET_LOAD_SCRIPT)
+                .arg(&self.data.key)
+                .arg(self.counter)
+                .arg(val)
+                .arg(&self.separator)
+                .invoke(&mut conn)
+        })
+        .expect("Could not execute script")
     }
 
     /// Loads the value from the redis server.
@@ -179,18 +265,21 @@ where
     /// Mostly used for synchronization. Reset the counter to order from redis or 0.
     pub fn load(&mut self) {
         let mut conn = self.data.client.get_connection().unwrap();
-        let res: (Option<String>, Option<usize>) = redis::Script::new(LOAD_SCRIPT)
-            .arg(&self.data.key)
-            .invoke(&mut conn)
-            .expect("Could not execute script");
+        let res: (Option<Vec<u8>>, Option<usize>) = instrumented("acquire", &self.data.key, || {
+            redis::Script::new(LOAD_SCRIPT)
+                .arg(&self.data.key)
+                .arg(&self.separator)
+                .invoke(&mut conn)
+        })
+        .expect("Could not execute script");
 
         match res {
             (Some(v), Some(order)) => {
-                self.data.cache = Some(from_str(&v).unwrap());
+                self.data.cache = Some(self.data.decode_value(&v));
                 self.counter = order;
             }
             (Some(v), None) => {
-                self.data.cache = Some(from_str(&v).unwrap());
+                self.data.cache = Some(self.data.decode_value(&v));
                 self.counter = 0;
             }
             (None, Some(c)) => {
@@ -232,4 +321,119 @@ mod tests {
         clock_ordered.store(2).unwrap();
         assert_eq!(*clock_ordered, 2);
     }
+
+    #[test]
+    fn test_store_blocking_timeout() {
+        use crate::redis::ClockOrdered;
+        use crate::redis::Generic;
+        use std::time::Duration;
+
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let i32: Generic<i32> = Generic::new("test_clock_ordered_timeout", client.clone());
+        let mut clock_ordered = ClockOrdered::new(i32);
+        clock_ordered
+            .store_blocking_timeout(3, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(*clock_ordered, 3);
+    }
+
+    #[test]
+    fn test_store_blocking_timeout_contended() {
+        use crate::redis::{Backoff, ClockOrdered, ClockOrderedError, Generic};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::time::{Duration, Instant};
+
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let i32: Generic<i32> = Generic::new("test_clock_ordered_contended", client.clone());
+        let backoff = Backoff::new(Duration::from_millis(10), Duration::from_millis(10));
+        let mut clock_ordered = ClockOrdered::new(i32).with_backoff(backoff.without_jitter());
+        let done = AtomicBool::new(false);
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                // another instance keeps storing with a newer order
+                let mut conn = client.get_connection().unwrap();
+                while !done.load(Ordering::Relaxed) {
+                    redis::Cmd::incr("test_clock_ordered_contended:order", 1000)
+                        .query::<()>(&mut conn)
+                        .unwrap();
+                }
+            });
+
+            let start = Instant::now();
+            let res = clock_ordered.store_blocking_timeout(1, Duration::from_millis(200));
+            done.store(true, Ordering::Relaxed);
+            assert!(matches!(res, Err(ClockOrderedError::Timeout)), "{:?}", res);
+            // the attempts go on until the deadline
+            assert!(start.elapsed() >= Duration::from_millis(200));
+        });
+    }
+
+    #[test]
+    fn test_clock_ordered_separator() {
+        use crate::redis::ClockOrdered;
+        use crate::redis::Generic;
+
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut conn = client.get_connection().unwrap();
+        redis::cmd("DEL")
+            .arg("test_clock_ordered_separator")
+            .arg("test_clock_ordered_separator:order")
+            .arg("test_clock_ordered_separator/order")
+            .execute(&mut conn);
+
+        let i32: Generic<i32> = Generic::new("test_clock_ordered_separator", client.clone());
+        let mut clock_ordered = ClockOrdered::new(i32).with_separator("/");
+        clock_ordered.store(1).unwrap();
+        let order: Option<usize> = redis::Cmd::get("test_clock_ordered_separator/order")
+            .query(&mut conn)
+            .unwrap();
+        assert_eq!(order, Some(1));
+        let exists: bool = redis::Cmd::exists("test_clock_ordered_separator:order")
+            .query(&mut conn)
+            .unwrap();
+        assert!(!exists);
+    }
+
+    #[cfg(feature = "messagepack")]
+    #[test]
+    fn test_clock_ordered_messagepack() {
+        use crate::redis::ClockOrdered;
+        use crate::redis::Generic;
+
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let data: Generic<Vec<i32>> =
+            Generic::with_messagepack("test_clock_ordered_messagepack", client.clone());
+        let mut clock_ordered = ClockOrdered::new(data);
+        clock_ordered.store(vec![1, 2, 3]).unwrap();
+        assert_eq!(*clock_ordered, vec![1, 2, 3]);
+
+        // the other types read what the ClockOrdered stored and the other way around
+        let mut data: Generic<Vec<i32>> =
+            Generic::with_messagepack("test_clock_ordered_messagepack", client.clone());
+        assert_eq!(data.acquire(), &vec![1, 2, 3]);
+        data.store(vec![4]);
+        let clock_ordered = ClockOrdered::new(Generic::<Vec<i32>>::with_messagepack(
+            "test_clock_ordered_messagepack",
+            client,
+        ));
+        assert_eq!(*clock_ordered, vec![4]);
+    }
+
+    #[test]
+    fn test_clock_ordered_plain() {
+        use crate::redis::ClockOrdered;
+        use crate::redis::DString;
+
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let s = DString::with_plain("test_clock_ordered_plain", client.clone());
+        let mut clock_ordered = ClockOrdered::new(s);
+        clock_ordered.store("plain".to_string()).unwrap();
+
+        let mut conn = client.get_connection().unwrap();
+        let raw: String = redis::Cmd::get("test_clock_ordered_plain")
+            .query(&mut conn)
+            .unwrap();
+        assert_eq!(raw, "plain");
+    }
 }
@@ -0,0 +1,166 @@
+//! # Compression
+//! This module contains the encoding of values, before they are stored in Redis.
+//!
+//! Raw values are stored as plain JSON, like before. Compressed values are prefixed with a
+//! header byte, which can never be the first byte of a JSON document. So raw and compressed
+//! values can be read by the same instance and older values stay readable.
+//!
+//! With the feature `messagepack`, a [Generic](crate::redis::Generic) can store its value as
+//! [MessagePack](https://msgpack.org) instead of JSON. These values have no header byte, so they can be
+//! decoded by any MessagePack library, e.g. `msgpack.unpackb` in Python.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// The header byte for values compressed with gzip.
+#[cfg(feature = "compression")]
+const GZIP_HEADER: u8 = 0x01;
+/// The header byte for values compressed with zstd.
+#[cfg(feature = "compression")]
+const ZSTD_HEADER: u8 = 0x02;
+
+/// The compression algorithm, which is used to store a value in Redis.
+///
+/// The compression is opt-in per value, so small values are not penalized.
+/// Compressed algorithms are only available with the feature `compression`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Stores the serialized value as it is. (Default)
+    #[default]
+    Raw,
+    /// Compresses the serialized value with gzip.
+    #[cfg(feature = "compression")]
+    Gzip,
+    /// Compresses the serialized value with zstd.
+    #[cfg(feature = "compression")]
+    Zstd,
+}
+
+/// The format, in which a [Generic](crate::redis::Generic) stores its value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    /// JSON, which can be compressed. (Default)
+    #[default]
+    Json,
+    /// Strings as they are, see [encode_plain].
+    Plain,
+    /// MessagePack, see [encode_msgpack].
+    #[cfg(feature = "messagepack")]
+    MessagePack,
+}
+
+/// Serializes the value and compresses it with the given algorithm.
+pub(crate) fn encode<T: Serialize + ?Sized>(value: &T, compression: Compression) -> Vec<u8> {
+    let json = serde_json::to_vec(value).expect("Failed to serialize value");
+
+    match compression {
+        Compression::Raw => json,
+        #[cfg(feature = "compression")]
+        Compression::Gzip => {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(vec![GZIP_HEADER], flate2::Compression::default());
+            encoder.write_all(&json).expect("Failed to compress value");
+            encoder.finish().expect("Failed to compress value")
+        }
+        #[cfg(feature = "compression")]
+        Compression::Zstd => {
+            let mut bytes = vec![ZSTD_HEADER];
+            zstd::stream::copy_encode(json.as_slice(), &mut bytes, 0)
+                .expect("Failed to compress value");
+            bytes
+        }
+    }
+}
+
+/// Serializes the value like [encode] without compression, but stores strings without the JSON quotes and escapes.
+/// So the stored bytes are the string itself and can be manipulated by the string commands of Redis.
+pub(crate) fn encode_plain<T: Serialize + ?Sized>(value: &T) -> Vec<u8> {
+    match serde_json::to_value(value).expect("Failed to serialize value") {
+        serde_json::Value::String(s) => s.into_bytes(),
+        other => serde_json::to_vec(&other).expect("Failed to serialize value"),
+    }
+}
+
+/// Deserializes the bytes stored by [encode_plain] as string.
+pub(crate) fn decode_plain<T: DeserializeOwned>(bytes: &[u8]) -> T {
+    let s = String::from_utf8_lossy(bytes).into_owned();
+    serde_json::from_value(serde_json::Value::String(s)).expect("Failed to deserialize value")
+}
+
+/// Serializes the value as MessagePack without a header and without compression.
+///
+/// Structs are stored as maps with the field names as keys, so other languages do not need to know
+/// the order of the fields. Enums are stored like in JSON: unit variants as string and other
+/// variants as map with the name of the variant as single key.
+#[cfg(feature = "messagepack")]
+pub(crate) fn encode_msgpack<T: Serialize + ?Sized>(value: &T) -> Vec<u8> {
+    rmp_serde::to_vec_named(value).expect("Failed to serialize value")
+}
+
+/// Deserializes the bytes stored by [encode_msgpack].
+#[cfg(feature = "messagepack")]
+pub(crate) fn decode_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> T {
+    rmp_serde::from_slice(bytes).expect("Failed to deserialize value")
+}
+
+/// Decompresses the bytes if they have a header and deserializes the value.
+pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> T {
+    try_decode(bytes).expect("Failed to deserialize value")
+}
+
+/// Decodes the bytes like [decode], but returns None, if they are no JSON stored by [encode].
+pub(crate) fn try_decode<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    match bytes.first() {
+        #[cfg(feature = "compression")]
+        Some(&GZIP_HEADER) => {
+            let decoder = flate2::read::GzDecoder::new(&bytes[1..]);
+            serde_json::from_reader(decoder).ok()
+        }
+        #[cfg(feature = "compression")]
+        Some(&ZSTD_HEADER) => {
+            let json = zstd::stream::decode_all(&bytes[1..]).ok()?;
+            serde_json::from_slice(&json).ok()
+        }
+        _ => serde_json::from_slice(bytes).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw() {
+        let bytes = encode(&vec![1, 2, 3], Compression::Raw);
+        assert_eq!(bytes, b"[1,2,3]");
+        assert_eq!(decode::<Vec<i32>>(&bytes), vec![1, 2, 3]);
+        assert_eq!(try_decode::<Vec<i32>>(b"not json"), None);
+    }
+
+    #[test]
+    fn test_plain() {
+        let bytes = encode_plain("Hello \"World\"");
+        assert_eq!(bytes, b"Hello \"World\"");
+        assert_eq!(decode_plain::<String>(&bytes), "Hello \"World\"");
+    }
+
+    #[cfg(feature = "messagepack")]
+    #[test]
+    fn test_msgpack() {
+        let bytes = encode_msgpack(&vec![1, 2, 3]);
+        assert_eq!(bytes, [0x93, 0x01, 0x02, 0x03]);
+        assert_eq!(decode_msgpack::<Vec<i32>>(&bytes), vec![1, 2, 3]);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compressed() {
+        let value = "a".repeat(1000);
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let bytes = encode(&value, compression);
+            assert!(bytes.len() < value.len());
+            assert_eq!(decode::<String>(&bytes), value);
+        }
+    }
+}
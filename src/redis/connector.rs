@@ -0,0 +1,344 @@
+//! # Connector
+//! This module contains the connector, which creates the connections to Redis for all types.
+use crate::redis::instrumented;
+use redis::{ErrorKind, RedisResult};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The connector creates a new connection to Redis for the operations of the types.
+///
+/// It is created from a [redis::Client], so you can pass a client everywhere a connector is expected.
+/// With the feature `sentinel`, it can also resolve the current master through Redis Sentinel.
+///
+/// If you already hold a connection, it can be passed instead of a client, see [Connector::from_connection].
+///
+/// Optionally, reads can be routed to a replica with [Connector::with_replica]
+/// and connecting can be bounded with [Connector::with_timeout].
+#[derive(Clone)]
+pub struct Connector {
+    kind: ConnectorKind,
+    replica: Option<ConnectorKind>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Clone)]
+enum ConnectorKind {
+    Client(redis::Client),
+    Connection(Arc<Mutex<Option<redis::Connection>>>),
+    #[cfg(feature = "sentinel")]
+    Sentinel {
+        sentinel: Arc<Mutex<redis::sentinel::Sentinel>>,
+        master_name: String,
+        server_type: redis::sentinel::SentinelServerType,
+    },
+}
+
+impl ConnectorKind {
+    #[cfg(feature = "sentinel")]
+    fn sentinel<T: redis::IntoConnectionInfo>(
+        sentinels: Vec<T>,
+        master_name: &str,
+        server_type: redis::sentinel::SentinelServerType,
+    ) -> RedisResult<Self> {
+        Ok(Self::Sentinel {
+            sentinel: Arc::new(Mutex::new(redis::sentinel::Sentinel::build(sentinels)?)),
+            master_name: master_name.to_string(),
+            server_type,
+        })
+    }
+
+    /// Returns the client of the server. Sentinels are asked for the current server every time.
+    fn client(&self) -> RedisResult<redis::Client> {
+        match self {
+            Self::Client(client) => Ok(client.clone()),
+            Self::Connection(_) => Err((
+                ErrorKind::ClientError,
+                "The connector was created from a connection and has no client",
+            )
+                .into()),
+            #[cfg(feature = "sentinel")]
+            Self::Sentinel {
+                sentinel,
+                master_name,
+                server_type,
+            } => {
+                let mut sentinel = sentinel.lock().expect("Sentinel is poisoned");
+                match server_type {
+                    redis::sentinel::SentinelServerType::Master => {
+                        sentinel.master_for(master_name, None)
+                    }
+                    redis::sentinel::SentinelServerType::Replica => {
+                        sentinel.replica_for(master_name, None)
+                    }
+                }
+            }
+        }
+    }
+
+    fn get_connection(&self, timeout: Option<Duration>) -> RedisResult<ConnectionGuard> {
+        if let Self::Connection(slot) = self {
+            let conn = slot.lock().expect("Connection is poisoned").take();
+            return match conn {
+                Some(conn) => Ok(ConnectionGuard {
+                    conn: Some(conn),
+                    slot: Some(slot.clone()),
+                }),
+                None => Err((
+                    ErrorKind::ClientError,
+                    "The connection is in use by another operation",
+                )
+                    .into()),
+            };
+        }
+
+        let client = self.client()?;
+        let conn = match timeout {
+            Some(timeout) => client.get_connection_with_timeout(timeout),
+            None => client.get_connection(),
+        }?;
+        Ok(ConnectionGuard {
+            conn: Some(conn),
+            slot: None,
+        })
+    }
+}
+
+/// A connection to Redis, which is returned by the [Connector].
+///
+/// It dereferences to [redis::Connection] and can be used for all commands like it.
+/// If the connector was created with [Connector::from_connection], the connection is given back on drop,
+/// so it can be used by the next operation.
+pub struct ConnectionGuard {
+    conn: Option<redis::Connection>,
+    slot: Option<Arc<Mutex<Option<redis::Connection>>>>,
+}
+
+impl Deref for ConnectionGuard {
+    type Target = redis::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        self.conn.as_ref().expect("Connection should be there")
+    }
+}
+
+impl DerefMut for ConnectionGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.conn.as_mut().expect("Connection should be there")
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        if let (Some(slot), Some(conn)) = (&self.slot, self.conn.take()) {
+            // a poisoned slot is not used anymore, so the connection can be dropped
+            if let Ok(mut slot) = slot.lock() {
+                *slot = Some(conn);
+            }
+        }
+    }
+}
+
+/// The configuration of a read replica for a [Connector].
+#[derive(Clone)]
+pub struct ReplicaConfig {
+    kind: ConnectorKind,
+}
+
+impl ReplicaConfig {
+    /// Uses the given client to read from the replica.
+    pub fn new(replica: redis::Client) -> Self {
+        Self {
+            kind: ConnectorKind::Client(replica),
+        }
+    }
+
+    /// Asks the given sentinels for a replica of the master with the given name.
+    ///
+    /// Only available with the feature `sentinel`.
+    #[cfg(feature = "sentinel")]
+    pub fn sentinel<T: redis::IntoConnectionInfo>(
+        sentinels: Vec<T>,
+        master_name: &str,
+    ) -> RedisResult<Self> {
+        Ok(Self {
+            kind: ConnectorKind::sentinel(
+                sentinels,
+                master_name,
+                redis::sentinel::SentinelServerType::Replica,
+            )?,
+        })
+    }
+}
+
+impl Connector {
+    /// Creates a connector, which asks the given sentinels for the current master with the given name.
+    /// The master is resolved again for every new connection, so a failover is picked up automatically.
+    ///
+    /// Only available with the feature `sentinel`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dtypes::redis::{Connector, Di32};
+    ///
+    /// let connector = Connector::sentinel(
+    ///     vec!["redis://sentinel1:26379", "redis://sentinel2:26379"],
+    ///     "mymaster",
+    /// )
+    /// .unwrap();
+    /// let i32 = Di32::with_value(1, "test_sentinel", connector);
+    /// ```
+    #[cfg(feature = "sentinel")]
+    pub fn sentinel<T: redis::IntoConnectionInfo>(
+        sentinels: Vec<T>,
+        master_name: &str,
+    ) -> RedisResult<Self> {
+        Ok(Self {
+            kind: ConnectorKind::sentinel(
+                sentinels,
+                master_name,
+                redis::sentinel::SentinelServerType::Master,
+            )?,
+            replica: None,
+            timeout: None,
+        })
+    }
+
+    /// Routes read-only operations like [Generic::acquire](crate::redis::Generic::acquire),
+    /// [List::iter](crate::redis::List::iter) or [List::len](crate::redis::List::len) to the replica.
+    /// All writes and lock operations stay on the master, because they must be consistent.
+    ///
+    /// Beware that a replica lags behind the master, so the read values can be stale.
+    /// A value you just stored may not be visible on the replica yet.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dtypes::redis::{Connector, Di32, ReplicaConfig};
+    ///
+    /// let master = redis::Client::open("redis://master:6379").unwrap();
+    /// let replica = redis::Client::open("redis://replica:6379").unwrap();
+    /// let connector = Connector::from(master).with_replica(ReplicaConfig::new(replica));
+    /// let mut i32 = Di32::with_value(1, "test_replica", connector);
+    /// // may be stale
+    /// i32.acquire();
+    /// ```
+    pub fn with_replica(mut self, replica: ReplicaConfig) -> Self {
+        self.replica = Some(replica.kind);
+        self
+    }
+
+    /// Sets the timeout for establishing new connections to the master and the replica.
+    /// If Redis is unreachable, the connection fails after the timeout instead of blocking indefinitely.
+    ///
+    /// Beware that most operations of the types panic, if no connection can be established.
+    /// Locking a [Mutex](crate::redis::Mutex) returns [LockError::NoConnection](crate::redis::LockError::NoConnection) instead.
+    ///
+    /// The timeout is not applied to async connections.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dtypes::redis::{Connector, Di32};
+    /// use std::time::Duration;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let connector = Connector::from(client).with_timeout(Duration::from_secs(1));
+    /// let i32 = Di32::with_value(1, "test_timeout", connector);
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Creates a connector, which uses the given connection for all operations instead of connecting again.
+    /// This is useful, if the connections are managed centrally, e.g. in test harnesses.
+    ///
+    /// The connection is used by one operation at a time. While it is in use, e.g. by a
+    /// [Mutex](crate::redis::Mutex), which holds its connection, other operations fail
+    /// with an error or panic. So types, which need multiple connections at once,
+    /// like the lock types or [Generic::wait_for](crate::redis::Generic::wait_for),
+    /// need a connector with a client.
+    ///
+    /// A connection can be passed everywhere a connector is expected, too.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::{Connector, Di32};
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let conn = client.get_connection().unwrap();
+    /// let connector = Connector::from_connection(conn);
+    /// let mut i32 = Di32::with_value(1, "test_from_connection", connector.clone());
+    /// let other = Di32::with_load("test_from_connection", connector);
+    /// assert_eq!(other, 1);
+    /// ```
+    pub fn from_connection(conn: redis::Connection) -> Self {
+        Self {
+            kind: ConnectorKind::Connection(Arc::new(Mutex::new(Some(conn)))),
+            replica: None,
+            timeout: None,
+        }
+    }
+
+    /// Returns a new connection to the master.
+    /// If the connector was created with [Connector::from_connection], the given connection is returned.
+    pub fn get_connection(&self) -> RedisResult<ConnectionGuard> {
+        self.kind.get_connection(self.timeout)
+    }
+
+    /// Returns a new async connection to the master.
+    ///
+    /// Only available with the feature `async`.
+    #[cfg(feature = "async")]
+    pub async fn get_async_connection(&self) -> RedisResult<redis::aio::Connection> {
+        self.kind.client()?.get_async_connection().await
+    }
+
+    /// Sends a PING to the master and returns the round-trip time.
+    /// Returns None, if Redis is not reachable.
+    ///
+    /// It is a cheap liveness probe, which does not store or load any value.
+    /// The time includes establishing the connection, so it is bounded by [Connector::with_timeout].
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Connector;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let connector = Connector::from(client);
+    /// assert!(connector.ping().is_some());
+    /// ```
+    pub fn ping(&self) -> Option<Duration> {
+        let start = Instant::now();
+        instrumented("ping", "", || {
+            let mut conn = self.get_connection()?;
+            redis::cmd("PING").query::<String>(&mut conn)
+        })
+        .ok()?;
+        Some(start.elapsed())
+    }
+
+    /// Returns a new connection for read-only operations.
+    /// This is the replica, if there is one configured, otherwise the master.
+    pub fn get_read_connection(&self) -> RedisResult<ConnectionGuard> {
+        match &self.replica {
+            Some(replica) => replica.get_connection(self.timeout),
+            None => self.get_connection(),
+        }
+    }
+}
+
+impl From<redis::Connection> for Connector {
+    fn from(conn: redis::Connection) -> Self {
+        Self::from_connection(conn)
+    }
+}
+
+impl From<redis::Client> for Connector {
+    fn from(client: redis::Client) -> Self {
+        Self {
+            kind: ConnectorKind::Client(client),
+            replica: None,
+            timeout: None,
+        }
+    }
+}
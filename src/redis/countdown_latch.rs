@@ -0,0 +1,169 @@
+//! # Countdown Latch
+//! This module contains the countdown latch type.
+use crate::redis::{instrumented, Connector};
+use redis::RedisResult;
+use std::time::{Duration, Instant};
+
+/// The count down script.
+/// It is used to decrement the counter and notify all waiting instances, when it reaches zero.
+/// Both are done in one script, so no notification can be missed.
+/// The counter never gets below zero.
+///
+/// Takes 1 Argument:
+/// 1. The key of the latch.
+const COUNT_DOWN_SCRIPT: &str = r#"
+local val = tonumber(redis.call("GET", ARGV[1]) or "0")
+if val <= 0 then
+    return 0
+end
+val = redis.call("DECR", ARGV[1])
+if val == 0 then
+    redis.call("PUBLISH", ARGV[1] .. ":done", 1)
+end
+return val"#;
+
+/// A distributed countdown latch.
+///
+/// Some instances count down, until the counter reaches zero. Other instances wait for it.
+/// Unlike a barrier, the participants do not wait for each other.
+/// So it can be used for fan-in coordination, e.g. to wait until K tasks are finished.
+///
+/// The waiting instances are notified via pub/sub, so they do not poll Redis.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::CountdownLatch;
+/// use std::thread;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut latch = CountdownLatch::new(2, "test_latch_example", client.clone());
+/// latch.reset(2);
+///
+/// thread::scope(|s| {
+///     for _ in 0..2 {
+///         let client = client.clone();
+///         s.spawn(move || {
+///             let mut latch = CountdownLatch::new(2, "test_latch_example", client);
+///             latch.count_down();
+///         });
+///     }
+///     latch.wait();
+/// });
+/// assert_eq!(latch.count(), 0);
+/// ```
+pub struct CountdownLatch {
+    key: String,
+    client: Connector,
+}
+
+impl CountdownLatch {
+    /// Creates a new CountdownLatch with the given count.
+    /// If the latch already exists in Redis, the stored count is kept,
+    /// so all participants can create it with the same count.
+    pub fn new(count: usize, key: &str, client: impl Into<Connector>) -> Self {
+        let s = Self {
+            key: key.to_string(),
+            client: client.into(),
+        };
+
+        let mut conn = s.client.get_connection().unwrap();
+        instrumented("new", &s.key, || {
+            redis::cmd("SET")
+                .arg(&s.key)
+                .arg(count)
+                .arg("NX")
+                .query::<()>(&mut conn)
+        })
+        .expect("Failed to create latch");
+        s
+    }
+
+    /// Sets the counter to the given count, regardless of the stored count.
+    pub fn reset(&mut self, count: usize) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("reset", &self.key, || {
+            redis::Cmd::set(&self.key, count).query::<()>(&mut conn)
+        })
+        .expect("Failed to reset latch");
+    }
+
+    /// Decrements the counter and returns the remaining count.
+    /// If the counter reaches zero, all waiting instances are released.
+    pub fn count_down(&mut self) -> usize {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("count_down", &self.key, || {
+            redis::Script::new(COUNT_DOWN_SCRIPT)
+                .arg(&self.key)
+                .invoke(&mut conn)
+        })
+        .expect("Failed to count down")
+    }
+
+    /// Returns the current count.
+    pub fn count(&self) -> usize {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("count", &self.key, || {
+            redis::Cmd::get(&self.key).query::<Option<usize>>(&mut conn)
+        })
+        .expect("Failed to get count")
+        .unwrap_or(0)
+    }
+
+    /// Blocks until the counter reaches zero.
+    pub fn wait(&mut self) {
+        self.wait_until(None);
+    }
+
+    /// Blocks until the counter reaches zero or the timeout expires.
+    /// Returns true, if the counter reached zero.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> bool {
+        self.wait_until(Some(Instant::now() + timeout))
+    }
+
+    fn wait_until(&mut self, deadline: Option<Instant>) -> bool {
+        let mut conn = self.client.get_connection().unwrap();
+        let mut pubsub = conn.as_pubsub();
+        let channel = format!("{}:done", self.key);
+        instrumented("wait", &self.key, || pubsub.subscribe(&channel))
+            .expect("Failed to subscribe to latch");
+
+        // the count is checked after subscribing, so no notification can be missed
+        while self.count() > 0 {
+            let timeout = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(timeout) if !timeout.is_zero() => Some(timeout),
+                    _ => return false,
+                },
+                None => None,
+            };
+            pubsub
+                .set_read_timeout(timeout)
+                .expect("Failed to set timeout");
+
+            let res: RedisResult<_> = pubsub.get_message();
+            match res {
+                Ok(_) => {}
+                Err(err) if err.is_timeout() => return self.count() == 0,
+                Err(err) => panic!("Failed to wait for latch: {err}"),
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_countdown_latch() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut latch = CountdownLatch::new(2, "test_countdown_latch", client);
+        latch.reset(2);
+        assert!(!latch.wait_timeout(Duration::from_millis(100)));
+        assert_eq!(latch.count_down(), 1);
+        assert_eq!(latch.count_down(), 0);
+        assert_eq!(latch.count_down(), 0);
+        assert!(latch.wait_timeout(Duration::from_millis(100)));
+    }
+}
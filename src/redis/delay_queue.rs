@@ -0,0 +1,152 @@
+//! # Delay Queue
+//! This module contains the delay queue type.
+use crate::redis::{instrumented, Connector};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+/// The push script.
+/// It is used to add an item with the score of the time in milliseconds, when it is ready.
+/// The time of the redis server is used, so all instances share the same clock.
+/// Every item gets a unique id as prefix, so the same item can be pushed multiple times.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the queue,
+/// 2. The delay in milliseconds,
+/// 3. The value to push.
+const PUSH_SCRIPT: &str = r#"
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+local id = redis.call("INCR", ARGV[1] .. ":ids")
+redis.call("ZADD", ARGV[1], now + ARGV[2], id .. ":" .. ARGV[3])
+return 1"#;
+
+/// The pop script.
+/// It is used to remove the item with the lowest score, if it is ready.
+/// Both operations are done in one script, so no item can be popped by two instances.
+///
+/// Takes 1 Argument:
+/// 1. The key of the queue.
+const POP_SCRIPT: &str = r#"
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+local res = redis.call("ZRANGEBYSCORE", ARGV[1], "-inf", now, "LIMIT", 0, 1)
+if next(res) == nil then
+    return nil
+end
+redis.call("ZREM", ARGV[1], res[1])
+return res[1]"#;
+
+/// A queue, which releases its items after a delay.
+///
+/// The items are stored in a sorted set in Redis with the time as score, when they are ready.
+/// So it can be used to schedule deferred work across multiple instances.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::DelayQueue;
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut queue = DelayQueue::new("test_delay_queue_example", client);
+/// queue.push(&1, Duration::from_millis(0));
+/// queue.push(&2, Duration::from_secs(60));
+/// assert_eq!(queue.len(), 2);
+/// assert_eq!(queue.pop_ready(), Some(1));
+/// assert_eq!(queue.pop_ready(), None);
+/// queue.clear();
+/// ```
+pub struct DelayQueue<T> {
+    key: String,
+    client: Connector,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> DelayQueue<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new DelayQueue.
+    /// Already stored items in redis are kept.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Adds the item to the queue, which will be ready after the given delay.
+    pub fn push(&mut self, val: &T, delay: Duration) {
+        let mut conn = self.client.get_connection().unwrap();
+        let val = serde_json::to_string(val).expect("Failed to serialize value");
+        instrumented("push", &self.key, || {
+            redis::Script::new(PUSH_SCRIPT)
+                .arg(&self.key)
+                .arg(delay.as_millis() as u64)
+                .arg(val)
+                .invoke::<()>(&mut conn)
+        })
+        .expect("Failed to push value");
+    }
+
+    /// Removes and returns the item, which is ready the longest.
+    /// Returns None, if no item is ready.
+    pub fn pop_ready(&mut self) -> Option<T> {
+        let mut conn = self.client.get_connection().unwrap();
+        let val: Option<String> = instrumented("pop_ready", &self.key, || {
+            redis::Script::new(POP_SCRIPT)
+                .arg(&self.key)
+                .invoke(&mut conn)
+        })
+        .expect("Failed to pop value");
+        val.map(|v| {
+            let (_, v) = v.split_once(':').expect("Failed to find id of value");
+            serde_json::from_str(v).expect("Failed to deserialize value")
+        })
+    }
+
+    /// Returns the number of items in the queue, ready or not.
+    pub fn len(&self) -> usize {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("len", &self.key, || {
+            redis::Cmd::zcard(&self.key).query(&mut conn)
+        })
+        .unwrap()
+    }
+
+    /// Returns true if the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all items from the queue
+    pub fn clear(&self) {
+        let mut conn = self.client.get_connection().unwrap();
+        let cmd = redis::Cmd::del(&[&self.key, &format!("{}:ids", self.key)]);
+        instrumented("clear", &self.key, || cmd.query::<()>(&mut conn))
+            .expect("Failed to clear queue");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_queue() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut queue = DelayQueue::new("test_delay_queue", client);
+        queue.clear();
+        queue.push(&"later".to_string(), Duration::from_millis(200));
+        queue.push(&"now".to_string(), Duration::from_millis(0));
+        queue.push(&"now".to_string(), Duration::from_millis(0));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.pop_ready(), Some("now".to_string()));
+        assert_eq!(queue.pop_ready(), Some("now".to_string()));
+        assert_eq!(queue.pop_ready(), None);
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(queue.pop_ready(), Some("later".to_string()));
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+//! # DVec
+//! This module contains the vector type, which is backed by a hash.
+use crate::redis::compression::{decode, encode};
+use crate::redis::{instrumented, Compression, Connector};
+use redis::RedisResult;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// The push script.
+/// It is used to append a value at the index of the current length and to increment the length.
+///
+/// Takes 2 Arguments:
+/// 1. The key of the vector,
+/// 2. The value to push.
+const PUSH_SCRIPT: &str = r#"
+local len = redis.call("HINCRBY", ARGV[1], "len", 1)
+redis.call("HSET", ARGV[1], len - 1, ARGV[2])
+return len"#;
+
+/// The set script.
+/// It is used to overwrite a value, only if the index is in bounds.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the vector,
+/// 2. The index of the value,
+/// 3. The value to set.
+const SET_SCRIPT: &str = r#"
+local len = tonumber(redis.call("HGET", ARGV[1], "len") or "0")
+if tonumber(ARGV[2]) >= len then
+    return 0
+end
+redis.call("HSET", ARGV[1], ARGV[2], ARGV[3])
+return 1"#;
+
+/// The pop script.
+/// It is used to remove the last value and to decrement the length.
+///
+/// Takes 1 Argument:
+/// 1. The key of the vector.
+const POP_SCRIPT: &str = r#"
+local len = tonumber(redis.call("HGET", ARGV[1], "len") or "0")
+if len == 0 then
+    return nil
+end
+local val = redis.call("HGET", ARGV[1], len - 1)
+redis.call("HDEL", ARGV[1], len - 1)
+redis.call("HSET", ARGV[1], "len", len - 1)
+return val"#;
+
+/// A vector that is stored in a Redis hash with the index as field.
+///
+/// In contrast to [List](crate::redis::List), which is a linked list in Redis,
+/// the access by index is O(1). So it fits workloads, which are dominated by random access.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::DVec;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut vec = DVec::new("test_dvec_example", client);
+/// vec.clear();
+/// vec.push(&1);
+/// vec.push(&2);
+/// assert!(vec.set(0, &3));
+/// assert!(!vec.set(2, &4));
+/// assert_eq!(vec.get(0), Some(3));
+/// assert_eq!(vec.len(), 2);
+/// assert_eq!(vec.pop(), Some(2));
+/// vec.clear();
+/// ```
+pub struct DVec<T> {
+    key: String,
+    client: Connector,
+    compression: Compression,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> DVec<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new DVec.
+    /// Already stored values in redis are kept.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+            compression: Compression::Raw,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Creates a new DVec like [DVec::new], which compresses all values with the given algorithm.
+    pub fn with_compression(
+        compression: Compression,
+        key: &str,
+        client: impl Into<Connector>,
+    ) -> Self {
+        let mut vec = Self::new(key, client);
+        vec.compression = compression;
+        vec
+    }
+
+    /// Returns the value at the index or None, if the index is out of bounds.
+    /// If a replica is configured in the [Connector], the value is read from the replica.
+    pub fn get(&self, index: usize) -> Option<T> {
+        let mut conn = self.client.get_read_connection().unwrap();
+        let res: RedisResult<Option<Vec<u8>>> = instrumented("get", &self.key, || {
+            redis::Cmd::hget(&self.key, index).query(&mut conn)
+        });
+        res.expect("Failed to get value").map(|v| decode(&v))
+    }
+
+    /// Overwrites the value at the index.
+    /// Returns false, if the index is out of bounds.
+    pub fn set(&mut self, index: usize, val: &T) -> bool {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: RedisResult<u8> = instrumented("set", &self.key, || {
+            redis::Script::new(SET_SCRIPT)
+                .arg(&self.key)
+                .arg(index)
+                .arg(encode(val, self.compression))
+                .invoke(&mut conn)
+        });
+        res.expect("Failed to set value") == 1
+    }
+
+    /// Appends the value to the end and returns the new length.
+    pub fn push(&mut self, val: &T) -> usize {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("push", &self.key, || {
+            redis::Script::new(PUSH_SCRIPT)
+                .arg(&self.key)
+                .arg(encode(val, self.compression))
+                .invoke(&mut conn)
+        })
+        .expect("Failed to push value")
+    }
+
+    /// Removes the last value and returns it.
+    pub fn pop(&mut self) -> Option<T> {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: RedisResult<Option<Vec<u8>>> = instrumented("pop", &self.key, || {
+            redis::Script::new(POP_SCRIPT)
+                .arg(&self.key)
+                .invoke(&mut conn)
+        });
+        res.expect("Failed to pop value").map(|v| decode(&v))
+    }
+
+    /// Returns the number of values.
+    /// If a replica is configured in the [Connector], the length is read from the replica.
+    pub fn len(&self) -> usize {
+        let mut conn = self.client.get_read_connection().unwrap();
+        let res: RedisResult<Option<usize>> = instrumented("len", &self.key, || {
+            redis::Cmd::hget(&self.key, "len").query(&mut conn)
+        });
+        res.expect("Failed to get length").unwrap_or(0)
+    }
+
+    /// Returns true if the vector is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all values
+    pub fn clear(&self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("clear", &self.key, || {
+            redis::Cmd::del(&self.key).query::<()>(&mut conn)
+        })
+        .expect("Failed to clear vector");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dvec() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut vec = DVec::new("test_dvec", client);
+        vec.clear();
+        assert!(vec.is_empty());
+        assert_eq!(vec.get(0), None::<String>);
+        assert_eq!(vec.push(&"a".to_string()), 1);
+        assert_eq!(vec.push(&"b".to_string()), 2);
+        assert!(vec.set(1, &"c".to_string()));
+        assert_eq!(vec.get(1), Some("c".to_string()));
+        assert_eq!(vec.pop(), Some("c".to_string()));
+        assert_eq!(vec.get(1), None);
+        assert_eq!(vec.len(), 1);
+    }
+}
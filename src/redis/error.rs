@@ -0,0 +1,42 @@
+//! # Error
+//! This module contains the error type, which unifies the errors of all types.
+use crate::redis::{ClockOrderedError, GenericError, LockError, RpcError, RwLockError};
+use thiserror::Error;
+
+/// The error of all types in this crate.
+///
+/// Every error of a single type can be converted into it, so callers can use `?`
+/// with one error type across [Mutex](crate::redis::Mutex), [RwLock](crate::redis::RwLock),
+/// [ClockOrdered](crate::redis::ClockOrdered) and the other types.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::{Di32, Error, Mutex, RwLock};
+///
+/// fn copy(from: &mut RwLock<i32>, to: &mut Mutex<i32>) -> Result<(), Error> {
+///     let mut read = from.read()?;
+///     let value = *read.acquire()?;
+///     to.lock()?.store(value)?;
+///     Ok(())
+/// }
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut from = RwLock::new(Di32::with_value(1, "test_error_example", client.clone()));
+/// let mut to = Mutex::new(Di32::new("test_error_example2", client));
+/// copy(&mut from, &mut to).unwrap();
+/// ```
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Generic(#[from] GenericError),
+    #[error(transparent)]
+    Lock(#[from] LockError),
+    #[error(transparent)]
+    RwLock(#[from] RwLockError),
+    #[error(transparent)]
+    ClockOrdered(#[from] ClockOrderedError),
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+    #[error("Error by Redis")]
+    Redis(#[from] redis::RedisError),
+}
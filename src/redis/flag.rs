@@ -0,0 +1,82 @@
+//! # Flag
+//! This module contains the flag type, which can be set atomically.
+use crate::redis::{instrumented, Connector};
+use redis::RedisResult;
+
+/// A distributed flag for one-shot guards like "has this migration run?".
+///
+/// In contrast to [Dbool](crate::redis::Dbool), [DFlag::test_and_set] sets the flag and returns
+/// the previous state in one atomic GETSET, so only one instance can observe the transition.
+/// The flag is stored as JSON `true` or `false`, so it can also be read by a Dbool.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::DFlag;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut flag = DFlag::new("test_flag_example", client);
+/// flag.clear();
+/// assert!(!flag.test_and_set());
+/// assert!(flag.test_and_set());
+/// assert!(flag.get());
+/// ```
+pub struct DFlag {
+    key: String,
+    client: Connector,
+}
+
+impl DFlag {
+    /// Creates a new DFlag.
+    /// An already stored flag in redis is kept. A missing flag is not set.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+        }
+    }
+
+    /// Sets the flag and returns, if it was set before.
+    pub fn test_and_set(&mut self) -> bool {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: RedisResult<Option<String>> = instrumented("test_and_set", &self.key, || {
+            redis::Cmd::getset(&self.key, "true").query(&mut conn)
+        });
+        res.expect("Failed to set flag").as_deref() == Some("true")
+    }
+
+    /// Clears the flag.
+    pub fn clear(&mut self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("clear", &self.key, || {
+            redis::Cmd::set(&self.key, "false").query::<()>(&mut conn)
+        })
+        .expect("Failed to clear flag");
+    }
+
+    /// Returns, if the flag is set.
+    /// If a replica is configured in the [Connector], it is read from the replica.
+    pub fn get(&self) -> bool {
+        let mut conn = self.client.get_read_connection().unwrap();
+        let res: RedisResult<Option<String>> = instrumented("get", &self.key, || {
+            redis::Cmd::get(&self.key).query(&mut conn)
+        });
+        res.expect("Failed to get flag").as_deref() == Some("true")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::Dbool;
+
+    #[test]
+    fn test_flag() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut flag = DFlag::new("test_flag", client.clone());
+        flag.clear();
+        assert!(!flag.get());
+        assert!(!flag.test_and_set());
+        assert!(flag.get());
+        assert_eq!(Dbool::with_load("test_flag", client), true);
+    }
+}
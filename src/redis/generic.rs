@@ -1,19 +1,127 @@
 //! This module contains the generic type.
-use crate::redis::apply_operator;
+use crate::redis::compression::{decode, decode_plain, encode, encode_plain, Encoding};
+#[cfg(feature = "messagepack")]
+use crate::redis::compression::{decode_msgpack, encode_msgpack};
+use crate::redis::{apply_operator, instrumented};
+use crate::redis::{Batch, Compression, ConnectionGuard, Connector, ReadOnly};
 use redis::{Commands, RedisResult};
 use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::{Debug, Display};
 use std::ops;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// The type tag script.
+/// It is used to register the type of the value, if no type is registered yet.
+/// Returns the registered type.
+///
+/// Takes 2 Arguments:
+/// 1. The key of the value,
+/// 2. The name of the type.
+const TYPE_TAG_SCRIPT: &str = r#"
+local current = redis.call("GET", ARGV[1] .. ":type")
+if current == false then
+    redis.call("SET", ARGV[1] .. ":type", ARGV[2])
+    return ARGV[2]
+end
+return current"#;
+
+/// The swap script.
+/// It is used to exchange the values of two keys atomically.
+/// A missing value is swapped too, so the other key is deleted.
+/// Returns the new values of both keys.
+///
+/// Takes 2 Arguments:
+/// 1. The key of the first value,
+/// 2. The key of the second value.
+const SWAP_SCRIPT: &str = r#"
+local a = redis.call("GET", ARGV[1])
+local b = redis.call("GET", ARGV[2])
+if b then
+    redis.call("SET", ARGV[1], b)
+else
+    redis.call("DEL", ARGV[1])
+end
+if a then
+    redis.call("SET", ARGV[2], a)
+else
+    redis.call("DEL", ARGV[2])
+end
+return {b, a}"#;
+
+/// Escapes the glob characters of the key, so it matches only itself in a PSUBSCRIBE pattern.
+fn escape_pattern(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+    for c in key.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The history script.
+/// It is used to append the stored value with the current time of the redis server to the history.
+/// The history is trimmed to the given number of entries, so it cannot grow forever.
+/// It is sent with EVAL in the same pipeline as the write, so it can be appended to every writing pipeline.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the value,
+/// 2. The stored value,
+/// 3. The maximum number of entries in the history.
+const HISTORY_SCRIPT: &str = r#"
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+redis.call("RPUSH", ARGV[1] .. ":history", string.format("%d", now) .. ":" .. ARGV[2])
+redis.call("LTRIM", ARGV[1] .. ":history", -tonumber(ARGV[3]), -1)
+return 1"#;
+
+#[derive(Error, Debug)]
+pub enum GenericError {
+    #[error("No value stored in Redis")]
+    NoValue,
+    #[error("Timeout while waiting for the value")]
+    Timeout,
+    #[error("Type mismatch: expected {expected}, but {found} is stored")]
+    TypeMismatch { expected: String, found: String },
+    #[error("Only {acknowledged} of {required} replicas acknowledged the write in time")]
+    NotReplicated {
+        acknowledged: usize,
+        required: usize,
+    },
+    #[error("The value read back differs from the stored value")]
+    VerifyFailed,
+    #[error("Error by Redis")]
+    Redis(#[from] redis::RedisError),
+}
 
 /// The generic type is used to implement the common methods for all types.
 ///
 /// The generic type is not meant to be used directly. Instead use one of the aliases.
 ///
 /// Mostly you will interact with the methods [Generic::store], [Generic::acquire] and [Generic::into_inner].
+///
+/// # Cache
+///
+/// The type dereferences to the cached value, so `*value` and the operators use the cache without a round trip.
+/// Dereferencing panics, if the cache is empty. The cache is filled by the constructors as follows:
+///
+/// * [Generic::new] and [Generic::with_compression] and [Generic::with_cache_ttl] leave the cache empty.
+/// * [Generic::with_load] leaves the cache empty, if there is no value stored in Redis.
+/// * [Generic::with_value] and [Generic::with_value_default] always fill the cache.
+///
+/// After [Generic::store] or a successful [Generic::acquire] the cache is filled.
+/// Use [Generic::cached] to access the cache without panicking.
 pub struct Generic<T> {
     pub(crate) cache: Option<T>,
     pub(crate) key: String,
-    pub(crate) client: redis::Client,
+    pub(crate) client: Connector,
+    pub(crate) compression: Compression,
+    pub(crate) encoding: Encoding,
+    pub(crate) cache_ttl: Option<Duration>,
+    pub(crate) fetched_at: Option<Instant>,
+    pub(crate) history: Option<usize>,
 }
 
 impl<T> Generic<T>
@@ -22,7 +130,9 @@ where
 {
     /// The new method creates a new instance of the type.
     /// It does not load or store any value in Redis.
-    /// It only creates the instance.
+    /// It only creates the instance, so the cache is empty and dereferencing it panics.
+    ///
+    /// The field name can be a `&str` or an owned `String`, so a generated key does not need to be borrowed.
     ///
     /// # Example
     ///
@@ -35,17 +145,149 @@ where
     /// let i32 = i32 + i32::with_value(2, "test_add2", client);
     /// assert_eq!(i32, 3);
     /// ```
-    pub fn new(field_name: &str, client: redis::Client) -> Generic<T> {
+    pub fn new(field_name: impl Into<String>, client: impl Into<Connector>) -> Generic<T> {
         Generic {
             cache: None,
-            key: field_name.to_string(),
-            client,
+            key: field_name.into(),
+            client: client.into(),
+            compression: Compression::Raw,
+            encoding: Encoding::Json,
+            cache_ttl: None,
+            fetched_at: None,
+            history: None,
         }
     }
 
+    /// The with_compression method creates a new instance of the type like [Generic::new].
+    /// All values are compressed with the given algorithm before they are stored in Redis.
+    ///
+    /// Values stored without compression can still be loaded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "compression")] {
+    /// use dtypes::redis::{Compression, DString};
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut s = DString::with_compression(Compression::Gzip, "test_compression", client.clone());
+    /// s.store("a".repeat(1000));
+    /// assert_eq!(DString::with_load("test_compression", client).acquire(), &"a".repeat(1000));
+    /// # }
+    /// ```
+    pub fn with_compression(
+        compression: Compression,
+        field_name: impl Into<String>,
+        client: impl Into<Connector>,
+    ) -> Generic<T> {
+        let mut new_type = Self::new(field_name, client);
+
+        new_type.compression = compression;
+        new_type
+    }
+
+    /// The with_cache_ttl method creates a new instance of the type like [Generic::new].
+    /// The cached value is trusted for the given time, so [Generic::acquire] only loads the value
+    /// from Redis, if it was loaded or stored longer ago than the ttl.
+    ///
+    /// This reduces the load on Redis for values, which change rarely, but the value can be stale.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use std::time::Duration;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut config = i32::with_cache_ttl(Duration::from_secs(60), "test_cache_ttl", client.clone());
+    /// config.store(1);
+    /// i32::with_value(2, "test_cache_ttl", client);
+    /// // the cache is trusted for 60 seconds
+    /// assert_eq!(config.acquire(), &1);
+    /// ```
+    pub fn with_cache_ttl(
+        ttl: Duration,
+        field_name: impl Into<String>,
+        client: impl Into<Connector>,
+    ) -> Generic<T> {
+        let mut new_type = Self::new(field_name, client);
+
+        new_type.cache_ttl = Some(ttl);
+        new_type
+    }
+
+    /// The with_messagepack method creates a new instance of the type like [Generic::new],
+    /// which stores the value as [MessagePack](https://msgpack.org) instead of JSON.
+    ///
+    /// The stored bytes are exactly the output of `rmp_serde::to_vec_named` without any header,
+    /// so services in other languages can decode them with their MessagePack library,
+    /// e.g. `msgpack.unpackb` in Python. Structs are stored as maps with the field names as keys.
+    /// Beware that the value cannot be read by an instance, which expects JSON, and the other way around.
+    ///
+    /// Only available with the feature `messagepack`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "messagepack")] {
+    /// use dtypes::redis::Generic;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut v: Generic<Vec<i32>> = Generic::with_messagepack("test_messagepack", client.clone());
+    /// v.store(vec![1, 2, 3]);
+    /// let raw: Vec<u8> = redis::cmd("GET").arg("test_messagepack").query(&mut client.get_connection().unwrap()).unwrap();
+    /// assert_eq!(raw, [0x93, 0x01, 0x02, 0x03]);
+    /// # }
+    /// ```
+    #[cfg(feature = "messagepack")]
+    pub fn with_messagepack(
+        field_name: impl Into<String>,
+        client: impl Into<Connector>,
+    ) -> Generic<T> {
+        let mut new_type = Self::new(field_name, client);
+
+        new_type.encoding = Encoding::MessagePack;
+        new_type
+    }
+
+    /// The with_history method creates a new instance of the type like [Generic::new].
+    /// Every stored value is also appended with a timestamp to the list `<key>:history`,
+    /// which keeps the latest `max_len` entries. Read it with [Generic::history].
+    ///
+    /// This gives a lightweight change log for debugging, but every store needs a script instead of a plain SET.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// # redis::cmd("DEL").arg("test_history:history").execute(&mut client.get_connection().unwrap());
+    /// let mut i32 = i32::with_history(2, "test_history", client);
+    /// i32.store(1);
+    /// i32.store(2);
+    /// i32.store(3);
+    /// let values: Vec<_> = i32.history(10).into_iter().map(|(_, v)| v).collect();
+    /// assert_eq!(values, [2, 3]);
+    /// ```
+    pub fn with_history(
+        max_len: usize,
+        field_name: impl Into<String>,
+        client: impl Into<Connector>,
+    ) -> Generic<T> {
+        let mut new_type = Self::new(field_name, client);
+
+        new_type.history = Some(max_len.max(1));
+        new_type
+    }
+
     /// The with_value method creates a new instance of the type.
     /// If a value is already stored in Redis, it will be overwritten.
-    pub fn with_value(value: T, field_name: &str, client: redis::Client) -> Generic<T> {
+    pub fn with_value(
+        value: T,
+        field_name: impl Into<String>,
+        client: impl Into<Connector>,
+    ) -> Generic<T> {
         let mut new_type = Self::new(field_name, client);
 
         new_type.store(value);
@@ -54,21 +296,70 @@ where
 
     /// The with_value_load method creates a new instance of the type.
     /// It loads the value from Redis.
-    /// If there is no value stored in Redis, it stores a None in cache.
-    pub fn with_load(field_name: &str, client: redis::Client) -> Generic<T> {
+    /// If there is no value stored in Redis, it stores a None in cache, so dereferencing it panics.
+    pub fn with_load(field_name: impl Into<String>, client: impl Into<Connector>) -> Generic<T> {
         let mut new_type = Self::new(field_name, client);
 
-        new_type.cache = new_type.try_get();
+        new_type.cache = new_type.try_get(new_type.get_read_conn());
         new_type
     }
 
+    /// The with_type_check method creates a new instance of the type and loads the value like [Generic::with_load].
+    ///
+    /// Before, it registers the name of the type at `<key>:type`, if no type is registered yet.
+    /// If another type is registered, it returns [GenericError::TypeMismatch] instead of panicking,
+    /// when the value cannot be deserialized. This catches key collisions between services early.
+    ///
+    /// The name of the type is taken from [std::any::type_name], so all services should be built
+    /// with the same compiler to get the same names.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::{DString, Di32, GenericError};
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// # redis::cmd("DEL").arg("test_type_check:type").execute(&mut client.get_connection().unwrap());
+    /// let i32 = Di32::with_type_check("test_type_check", client.clone()).unwrap();
+    /// let res = DString::with_type_check("test_type_check", client);
+    /// assert!(matches!(res, Err(GenericError::TypeMismatch { .. })));
+    /// ```
+    pub fn with_type_check(
+        field_name: impl Into<String>,
+        client: impl Into<Connector>,
+    ) -> Result<Generic<T>, GenericError> {
+        let mut new_type = Self::new(field_name, client);
+        let expected = std::any::type_name::<T>();
+
+        let mut conn = new_type.get_conn();
+        let found: String = instrumented("type_check", &new_type.key, || {
+            redis::Script::new(TYPE_TAG_SCRIPT)
+                .arg(&new_type.key)
+                .arg(expected)
+                .invoke(&mut conn)
+        })?;
+        if found != expected {
+            return Err(GenericError::TypeMismatch {
+                expected: expected.to_string(),
+                found,
+            });
+        }
+
+        new_type.cache = new_type.try_get(new_type.get_read_conn());
+        Ok(new_type)
+    }
+
     /// The with_value_default method creates a new instance of the type.
     /// If the value is not already stored in Redis, it will be stored.
     /// If the value is already stored in Redis, it will be loaded and your given value will be ignored.
-    pub fn with_value_default(value: T, field_name: &str, client: redis::Client) -> Generic<T> {
+    pub fn with_value_default(
+        value: T,
+        field_name: impl Into<String>,
+        client: impl Into<Connector>,
+    ) -> Generic<T> {
         let mut new_type = Self::new(field_name, client);
 
-        let v = new_type.try_get();
+        let v = new_type.try_get(new_type.get_conn());
         if v.is_none() {
             new_type.store(value);
         } else {
@@ -82,16 +373,109 @@ where
     pub fn store(&mut self, value: T) {
         let value = self.set(value);
         self.cache = Some(value);
+        self.fetched_at = Some(Instant::now());
+    }
+
+    /// The store_durable method sets the value like [Generic::store] and waits with WAIT,
+    /// until the given number of replicas acknowledged the write or the timeout is reached.
+    /// So the value survives a failover to one of these replicas.
+    ///
+    /// Returns [GenericError::NotReplicated], if not enough replicas acknowledged the write in time.
+    /// The value is stored on the master and cached anyway, because WAIT cannot roll back the write.
+    /// A zero timeout waits forever.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use std::time::Duration;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut i32 = i32::new("test_store_durable", client);
+    /// i32.store_durable(1, 0, Duration::from_millis(100)).unwrap();
+    /// assert_eq!(i32, 1);
+    /// ```
+    pub fn store_durable(
+        &mut self,
+        value: T,
+        replicas: usize,
+        timeout: Duration,
+    ) -> Result<(), GenericError> {
+        let mut conn = self.get_conn();
+        let v = self.encode_value(&value);
+        // WAIT is not allowed in a transaction, but it only needs to run on the same connection
+        let (acknowledged,): (usize,) = instrumented("store_durable", &self.key, || {
+            let mut pipe = redis::pipe();
+            pipe.set(&self.key, &v).ignore();
+            self.add_history(&mut pipe, &v);
+            pipe.cmd("WAIT")
+                .arg(replicas)
+                .arg(timeout.as_millis() as u64)
+                .query(&mut conn)
+        })?;
+        self.cache = Some(value);
+        self.fetched_at = Some(Instant::now());
+
+        if acknowledged < replicas {
+            return Err(GenericError::NotReplicated {
+                acknowledged,
+                required: replicas,
+            });
+        }
+        Ok(())
+    }
+
+    /// The store_verified method sets the value like [Generic::store] and reads it back afterward.
+    /// Returns [GenericError::VerifyFailed], if the read value differs from the written one,
+    /// e.g. because another instance overwrote it in between or the value was dropped.
+    ///
+    /// The values are compared by their serialized form. On a mismatch, the cache is filled with the read value.
+    /// Both commands are sent in one pipeline, but it needs twice the work of [Generic::store] in Redis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut i32 = i32::new("test_store_verified", client);
+    /// i32.store_verified(1).unwrap();
+    /// assert_eq!(i32, 1);
+    /// ```
+    pub fn store_verified(&mut self, value: T) -> Result<(), GenericError> {
+        let mut conn = self.get_conn();
+        let v = self.encode_value(&value);
+        let (stored,): (Option<Vec<u8>>,) = instrumented("store_verified", &self.key, || {
+            let mut pipe = redis::pipe();
+            pipe.set(&self.key, &v).ignore();
+            self.add_history(&mut pipe, &v);
+            pipe.get(&self.key).query(&mut conn)
+        })?;
+        self.verify(value, &v, stored)
+    }
+
+    /// Compares the read bytes with the written ones and fills the cache with the read value.
+    fn verify(
+        &mut self,
+        value: T,
+        written: &[u8],
+        stored: Option<Vec<u8>>,
+    ) -> Result<(), GenericError> {
+        self.fetched_at = Some(Instant::now());
+
+        if stored.as_deref() != Some(written) {
+            self.cache = stored.map(|s| self.decode_value(&s));
+            return Err(GenericError::VerifyFailed);
+        }
+        self.cache = Some(value);
+        Ok(())
     }
 
     /// The set method sets the value of the type in redis.
     /// It does not update the cache.
     /// This is useful if you want to store a value in redis without updating the cache.
     fn set(&self, value: T) -> T {
-        let mut conn = self.get_conn();
-        let v = serde_json::to_string(&value).expect("Failed to serialize value");
-        let res: RedisResult<()> = conn.set(&self.key, v);
-        res.expect("Failed to set value");
+        self.write(self.encode_value(&value));
         value
     }
 
@@ -100,14 +484,66 @@ where
         if self.cache.is_none() {
             return;
         }
+        self.write(self.encode_value(&self.cache));
+    }
+
+    /// Writes the encoded value to redis and appends it to the history, if it is enabled.
+    fn write(&self, v: Vec<u8>) {
         let mut conn = self.get_conn();
-        let v = serde_json::to_string(&self.cache).expect("Failed to serialize value");
-        let res: RedisResult<()> = conn.set(&self.key, v);
+        let res: RedisResult<()> = instrumented("store", &self.key, || {
+            if self.history.is_none() {
+                return conn.set(&self.key, v);
+            }
+            let mut pipe = redis::pipe();
+            pipe.atomic().set(&self.key, &v).ignore();
+            self.add_history(&mut pipe, &v);
+            pipe.query(&mut conn)
+        });
         res.expect("Failed to set value");
     }
 
+    /// Adds the history script to the pipeline, which writes the encoded value, if the history is enabled.
+    /// All writing methods call it, so every stored value appears in the history.
+    fn add_history(&self, pipe: &mut redis::Pipeline, v: &[u8]) {
+        if let Some(max_len) = self.history {
+            pipe.cmd("EVAL")
+                .arg(HISTORY_SCRIPT)
+                .arg(0)
+                .arg(&self.key)
+                .arg(v)
+                .arg(max_len)
+                .ignore();
+        }
+    }
+
+    /// The history method returns the latest stored values with the time of the redis server, when they were stored.
+    /// The entries are ordered from the oldest to the newest and at most `limit` entries are returned.
+    ///
+    /// The history is only written by instances created with [Generic::with_history].
+    pub fn history(&self, limit: usize) -> Vec<(SystemTime, T)> {
+        if limit == 0 {
+            return Vec::new();
+        }
+        let mut conn = self.get_read_conn();
+        let entries: Vec<Vec<u8>> = instrumented("history", &self.key, || {
+            conn.lrange(format!("{}:history", self.key), -(limit as isize), -1)
+        })
+        .expect("Failed to load history");
+        entries
+            .iter()
+            .filter_map(|entry| {
+                let pos = entry.iter().position(|&b| b == b':')?;
+                let millis: u64 = std::str::from_utf8(&entry[..pos]).ok()?.parse().ok()?;
+                let time = UNIX_EPOCH + Duration::from_millis(millis);
+                Some((time, self.decode_value(&entry[pos + 1..])))
+            })
+            .collect()
+    }
+
     /// The acquire method returns a reference to the value stored in the type.
     /// Loads it from the redis directly.
+    /// If a replica is configured in the [Connector], it is loaded from the replica.
+    /// If a cache ttl is set with [Generic::with_cache_ttl], the cached value is returned while it is fresh.
     ///
     /// # Example
     ///
@@ -120,19 +556,110 @@ where
     /// assert_eq!(i32.acquire(), &3);
     /// ```
     pub fn acquire(&mut self) -> &T {
-        self.cache = self.try_get();
+        if !self.is_cache_fresh() {
+            self.cache = self.try_get(self.get_read_conn());
+            self.fetched_at = Some(Instant::now());
+        }
         self.cache.as_ref().unwrap()
     }
 
-    fn try_get(&self) -> Option<T> {
-        let mut conn = self.get_conn();
-        let res: RedisResult<String> = conn.get(&self.key);
-        match res {
-            Ok(v) => {
-                let v: T = serde_json::from_str(&v).expect("Failed to deserialize value");
-                Some(v)
+    /// The acquire_or method loads the value like [Generic::acquire],
+    /// but falls back to the default, if there is no value stored in Redis.
+    /// The default is only put into the cache and not stored in Redis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// # redis::cmd("DEL").arg("test_acquire_or").execute(&mut client.get_connection().unwrap());
+    /// let mut i32 = i32::new("test_acquire_or", client.clone());
+    /// assert_eq!(i32.acquire_or(5), &5);
+    /// assert_eq!(i32::with_load("test_acquire_or", client).cached(), None);
+    /// ```
+    pub fn acquire_or(&mut self, default: T) -> &T {
+        self.acquire_or_else(|| default)
+    }
+
+    /// The acquire_or_else method loads the value like [Generic::acquire_or],
+    /// but computes the default only, if there is no value stored in Redis.
+    pub fn acquire_or_else(&mut self, f: impl FnOnce() -> T) -> &T {
+        if !self.is_cache_fresh() {
+            self.cache = self.try_get(self.get_read_conn());
+            self.fetched_at = Some(Instant::now());
+        }
+        if self.cache.is_none() {
+            // the default is not loaded from Redis, so it should not be trusted by the cache ttl
+            self.fetched_at = None;
+        }
+        self.cache.get_or_insert_with(f)
+    }
+
+    /// The get_or_insert_with method loads the value like [Generic::acquire].
+    /// If there is no value stored in Redis, it is computed with the function and stored with SETNX.
+    ///
+    /// If another instance stored a value in the meantime, its value wins and is returned,
+    /// so the value is inserted only once, even if multiple instances compute it at the same time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// # redis::cmd("DEL").arg("test_get_or_insert_with").execute(&mut client.get_connection().unwrap());
+    /// let mut i32 = i32::new("test_get_or_insert_with", client.clone());
+    /// assert_eq!(i32.get_or_insert_with(|| 5), &5);
+    /// let mut other = i32::new("test_get_or_insert_with", client);
+    /// assert_eq!(other.get_or_insert_with(|| 6), &5);
+    /// ```
+    pub fn get_or_insert_with(&mut self, f: impl FnOnce() -> T) -> &T {
+        if self.is_cache_fresh() {
+            return self.cache.as_ref().unwrap();
+        }
+
+        let value = match self.try_get(self.get_read_conn()) {
+            Some(value) => value,
+            None => {
+                let mut conn = self.get_conn();
+                let v = self.encode_value(&f());
+                let res: RedisResult<(bool, Vec<u8>)> =
+                    instrumented("get_or_insert", &self.key, || {
+                        redis::pipe()
+                            .atomic()
+                            .set_nx(&self.key, &v)
+                            .get(&self.key)
+                            .query(&mut conn)
+                    });
+                let (inserted, stored) = res.expect("Failed to insert value");
+                if inserted {
+                    // only the inserted value is a change, a loaded one is in the history already
+                    let mut pipe = redis::pipe();
+                    self.add_history(&mut pipe, &v);
+                    instrumented("history", &self.key, || pipe.query::<()>(&mut conn))
+                        .expect("Failed to append history");
+                }
+                self.decode_value(&stored)
             }
-            Err(_) => None,
+        };
+        self.fetched_at = Some(Instant::now());
+        self.cache.insert(value)
+    }
+
+    fn is_cache_fresh(&self) -> bool {
+        match (self.cache_ttl, self.fetched_at, &self.cache) {
+            (Some(ttl), Some(fetched_at), Some(_)) => fetched_at.elapsed() < ttl,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn try_get(&self, mut conn: ConnectionGuard) -> Option<T> {
+        let res: RedisResult<Option<Vec<u8>>> =
+            instrumented("acquire", &self.key, || conn.get(&self.key));
+        match res {
+            Ok(Some(v)) => Some(self.decode_value(&v)),
+            _ => None,
         }
     }
 
@@ -154,22 +681,353 @@ where
             .client
             .get_connection()
             .expect("Failed to get connection");
-        let _: RedisResult<()> = conn.del(&self.key);
+        let _: RedisResult<()> = instrumented("delete", &self.key, || conn.del(&self.key));
         self.cache.take().expect("Failed to get value")
     }
 
+    /// The try_into_inner method returns the inner value like [Generic::into_inner] and deletes the key.
+    /// If the cache is empty, the instance is returned unchanged and the key is kept,
+    /// so it can be loaded with [Generic::acquire] and consumed again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32 = i32::with_value(3, "test_try_into_inner", client.clone());
+    /// let unloaded = i32::new("test_try_into_inner", client);
+    /// let mut unloaded = unloaded.try_into_inner().unwrap_err();
+    /// assert_eq!(unloaded.acquire(), &3);
+    /// assert_eq!(unloaded.try_into_inner().ok(), Some(3));
+    /// ```
+    // the instance is given back like Arc::try_unwrap does, so it is not boxed
+    #[allow(clippy::result_large_err)]
+    pub fn try_into_inner(self) -> Result<T, Self> {
+        if self.cache.is_none() {
+            return Err(self);
+        }
+        Ok(self.into_inner())
+    }
+
+    /// The update method modifies the stored value in place with the given function.
+    ///
+    /// The key is watched while the value is loaded, modified and stored in a transaction.
+    /// If the value was changed by another instance in the meantime, it is loaded again and the function is retried.
+    /// So the function can be called multiple times and should not have side effects.
+    ///
+    /// Returns [GenericError::NoValue], if there is no value stored in Redis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Generic;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = Generic::with_value(vec![1, 2], "test_update", client);
+    /// list.update(|v| v.push(3)).unwrap();
+    /// assert_eq!(list, vec![1, 2, 3]);
+    /// ```
+    pub fn update(&mut self, mut f: impl FnMut(&mut T)) -> Result<(), GenericError> {
+        let mut conn = self.get_conn();
+        let value: Option<T> = instrumented("update", &self.key, || {
+            redis::transaction(&mut conn, &[&self.key], |conn, pipe| {
+                let v: Option<Vec<u8>> = conn.get(&self.key)?;
+                let Some(v) = v else {
+                    return Ok(Some(None));
+                };
+
+                let mut value: T = self.decode_value(&v);
+                f(&mut value);
+                let v = self.encode_value(&value);
+                pipe.set(&self.key, &v).ignore();
+                self.add_history(pipe, &v);
+                let res: Option<()> = pipe.query(conn)?;
+                Ok(res.map(|_| Some(value)))
+            })
+        })?;
+
+        self.cache = Some(value.ok_or(GenericError::NoValue)?);
+        self.fetched_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// The wait_for method blocks until the stored value equals the target or the timeout expires.
+    /// The current value is checked first, so an already set value is not missed.
+    /// Afterwards, the cache contains the target.
+    ///
+    /// It subscribes to the keyspace notifications of the key, so it does not poll Redis.
+    /// Beware that the notifications must be enabled in Redis with `notify-keyspace-events`,
+    /// e.g. `CONFIG SET notify-keyspace-events K$`, otherwise only the first check is done.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::DString;
+    /// use std::time::Duration;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut status = DString::with_value("ready".to_string(), "test_wait_for", client);
+    /// status.wait_for("ready".to_string(), Duration::from_secs(1)).unwrap();
+    /// ```
+    pub fn wait_for(&mut self, target: T, timeout: Duration) -> Result<(), GenericError>
+    where
+        T: PartialEq,
+    {
+        let deadline = Instant::now() + timeout;
+        let mut conn = self.client.get_connection()?;
+        let mut pubsub = conn.as_pubsub();
+        let pattern = format!("__keyspace@*__:{}", escape_pattern(&self.key));
+        instrumented("wait_for", &self.key, || pubsub.psubscribe(&pattern))?;
+
+        // the value is checked after subscribing, so no change can be missed.
+        // It is read from the primary, because a replica may not have the notified change yet.
+        loop {
+            let value = self.try_get(self.client.get_connection()?);
+            if value.as_ref() == Some(&target) {
+                self.cache = value;
+                self.fetched_at = Some(Instant::now());
+                return Ok(());
+            }
+
+            let timeout = deadline
+                .checked_duration_since(Instant::now())
+                .filter(|timeout| !timeout.is_zero())
+                .ok_or(GenericError::Timeout)?;
+            pubsub.set_read_timeout(Some(timeout))?;
+            match pubsub.get_message() {
+                Ok(_) => {}
+                Err(err) if err.is_timeout() => return Err(GenericError::Timeout),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// The rename method moves the stored value to the new key without loading it.
+    /// The value at the new key is overwritten, if there is one. Use [Generic::rename_nx] to keep it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no value stored in Redis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut i32 = i32::with_value(3, "test_rename", client.clone());
+    /// i32.rename("test_rename2");
+    /// assert_eq!(i32::with_load("test_rename2", client.clone()), 3);
+    /// assert_eq!(i32::with_load("test_rename", client).cached(), None);
+    /// ```
+    pub fn rename(&mut self, new_key: &str) {
+        let mut conn = self.get_conn();
+        let res: RedisResult<()> =
+            instrumented("rename", &self.key, || conn.rename(&self.key, new_key));
+        res.expect("Failed to rename value");
+        self.key = new_key.to_string();
+    }
+
+    /// The rename_nx method moves the stored value to the new key like [Generic::rename],
+    /// but only if there is no value stored at the new key.
+    /// Returns true if the value was moved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no value stored in Redis.
+    pub fn rename_nx(&mut self, new_key: &str) -> bool {
+        let mut conn = self.get_conn();
+        let res: RedisResult<bool> =
+            instrumented("rename", &self.key, || conn.rename_nx(&self.key, new_key));
+        let renamed = res.expect("Failed to rename value");
+        if renamed {
+            self.key = new_key.to_string();
+        }
+        renamed
+    }
+
+    /// The persist method removes the expiry of the stored value with PERSIST, so it is kept permanently.
+    /// Returns true, if an expiry was removed, and false, if the value has no expiry or there is no value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut i32 = i32::with_value(3, "test_persist", client.clone());
+    /// redis::cmd("EXPIRE").arg("test_persist").arg(10).execute(&mut client.get_connection().unwrap());
+    /// assert!(i32.persist());
+    /// assert!(!i32.persist());
+    /// ```
+    pub fn persist(&mut self) -> bool {
+        let mut conn = self.get_conn();
+        let res: RedisResult<bool> = instrumented("persist", &self.key, || conn.persist(&self.key));
+        res.expect("Failed to persist value")
+    }
+
+    /// The copy_to method copies the stored value to the new key without loading it.
+    /// The value is only copied, if there is no value stored at the new key.
+    /// Returns true if the value was copied.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32 = i32::with_value(3, "test_copy_to", client.clone());
+    /// # redis::cmd("DEL").arg("test_copy_to2").execute(&mut client.get_connection().unwrap());
+    /// assert!(i32.copy_to("test_copy_to2"));
+    /// assert_eq!(i32::with_load("test_copy_to2", client), 3);
+    /// ```
+    pub fn copy_to(&self, new_key: &str) -> bool {
+        let mut conn = self.get_conn();
+        let res: RedisResult<bool> = instrumented("copy", &self.key, || {
+            redis::cmd("COPY")
+                .arg(&self.key)
+                .arg(new_key)
+                .query(&mut conn)
+        });
+        res.expect("Failed to copy value")
+    }
+
+    /// The swap method exchanges the stored values of both types atomically in one script,
+    /// so there is no moment, where both keys hold the same value or none.
+    /// Both caches are updated with the swapped values afterward.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::DString;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut active = DString::with_value("new".to_string(), "test_swap_active", client.clone());
+    /// let mut standby = DString::with_value("old".to_string(), "test_swap_standby", client);
+    /// active.swap(&mut standby);
+    /// assert_eq!(active, "old");
+    /// assert_eq!(standby, "new");
+    /// ```
+    pub fn swap(&mut self, other: &mut Generic<T>) {
+        let mut conn = self.get_conn();
+        let (mine, theirs): (Option<Vec<u8>>, Option<Vec<u8>>) =
+            instrumented("swap", &self.key, || {
+                redis::Script::new(SWAP_SCRIPT)
+                    .arg(&self.key)
+                    .arg(&other.key)
+                    .invoke(&mut conn)
+            })
+            .expect("Failed to swap values");
+
+        let mut pipe = redis::pipe();
+        if let Some(v) = &mine {
+            self.add_history(&mut pipe, v);
+        }
+        if let Some(v) = &theirs {
+            other.add_history(&mut pipe, v);
+        }
+        instrumented("history", &self.key, || pipe.query::<()>(&mut conn))
+            .expect("Failed to append history");
+
+        let now = Some(Instant::now());
+        self.cache = mine.map(|v| self.decode_value(&v));
+        self.fetched_at = now;
+        other.cache = theirs.map(|v| other.decode_value(&v));
+        other.fetched_at = now;
+    }
+
+    /// The batch method starts a [Batch] of arithmetic operations, which are computed locally
+    /// and stored with a single round trip on [Batch::commit].
+    pub fn batch(&mut self) -> Batch<'_, T>
+    where
+        T: Clone,
+    {
+        Batch::new(self)
+    }
+
+    /// The ping method checks, if Redis is reachable, like [Connector::ping].
+    /// Returns the round-trip time or None, if Redis is not reachable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32 = i32::new("test_ping", client);
+    /// assert!(i32.ping().is_some());
+    /// ```
+    pub fn ping(&self) -> Option<Duration> {
+        self.client.ping()
+    }
+
+    /// Encodes the value in the configured encoding and compression.
+    pub(crate) fn encode_value<V: Serialize + ?Sized>(&self, value: &V) -> Vec<u8> {
+        match self.encoding {
+            Encoding::Json => encode(value, self.compression),
+            Encoding::Plain => encode_plain(value),
+            #[cfg(feature = "messagepack")]
+            Encoding::MessagePack => encode_msgpack(value),
+        }
+    }
+
+    /// Decodes the bytes like they were encoded by [Generic::encode_value].
+    pub(crate) fn decode_value(&self, bytes: &[u8]) -> T {
+        match self.encoding {
+            Encoding::Json => decode(bytes),
+            Encoding::Plain => decode_plain(bytes),
+            #[cfg(feature = "messagepack")]
+            Encoding::MessagePack => decode_msgpack(bytes),
+        }
+    }
+
     /// The get_conn method returns a connection to Redis.
     // FIXME: This should store a persistent connection for performance.
-    pub(crate) fn get_conn(&self) -> redis::Connection {
+    pub(crate) fn get_conn(&self) -> ConnectionGuard {
         self.client
             .get_connection()
             .expect("Failed to get connection")
     }
 
-    /// The get method returns a reference to the value stored in the type.
+    /// The get_read_conn method returns a connection for read-only operations to Redis.
+    pub(crate) fn get_read_conn(&self) -> ConnectionGuard {
+        self.client
+            .get_read_connection()
+            .expect("Failed to get connection")
+    }
+
+    /// The cached method returns a reference to the value stored in the type.
+    /// Returns None, if the cache is empty, instead of panicking like the dereference.
     pub fn cached(&self) -> Option<&T> {
         self.cache.as_ref()
     }
+
+    /// The as_readonly method returns a view of the type, which can only load the value.
+    /// Pass it to functions, which should only observe the value. See [ReadOnly] for an example.
+    pub fn as_readonly(&self) -> ReadOnly<'_, T> {
+        ReadOnly::new(self)
+    }
+
+    /// The invalidate method empties the cache without touching the value in Redis.
+    /// So the next [Generic::acquire] loads the value again, even if a cache ttl is set,
+    /// and [Generic::cached] returns None until then.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut i32 = i32::with_value(1, "test_invalidate", client);
+    /// i32.invalidate();
+    /// assert_eq!(i32.cached(), None);
+    /// assert_eq!(i32.acquire(), &1);
+    /// ```
+    pub fn invalidate(&mut self) {
+        self.cache = None;
+        self.fetched_at = None;
+    }
 }
 
 impl<T> ops::Deref for Generic<T>
@@ -178,8 +1036,14 @@ where
 {
     type Target = T;
 
+    /// Returns the cached value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cache is empty. See [Generic::cached] for a non-panicking access.
     fn deref(&self) -> &Self::Target {
-        self.cached().expect("Failed to get value")
+        self.cached()
+            .expect("No cached value. Store or acquire a value before dereferencing it")
     }
 }
 
@@ -406,6 +1270,30 @@ impl<T: Debug> Debug for Generic<T> {
     }
 }
 
+/// Serializes the cached value like an `Option<T>`, so the logical value can be dumped
+/// as part of a larger struct. The key and the connection are not serialized.
+///
+/// There is no Deserialize implementation, because a connection cannot be restored.
+/// Deserialize the value itself and create the type with [Generic::with_value] instead.
+///
+/// # Example
+///
+/// ```
+/// use dtypes::redis::Di32 as i32;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let i32 = i32::with_value(3, "test_serialize", client);
+/// assert_eq!(serde_json::to_string(&i32).unwrap(), "3");
+/// ```
+impl<T: Serialize> Serialize for Generic<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.cache.serialize(serializer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -418,4 +1306,246 @@ mod tests {
         );
         assert_eq!(s1, 2);
     }
+
+    #[test]
+    fn test_update() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1 = Generic::with_value(vec![1], "test_update_generic", client.clone());
+        let mut s2: Generic<Vec<i32>> = Generic::new("test_update_generic", client.clone());
+        s1.update(|v| v.push(2)).unwrap();
+        s2.update(|v| v.push(3)).unwrap();
+        assert_eq!(s2, vec![1, 2, 3]);
+        assert_eq!(s1.acquire(), &vec![1, 2, 3]);
+
+        let mut s3: Generic<Vec<i32>> = Generic::new("test_update_generic_empty", client);
+        assert!(matches!(
+            s3.update(|v| v.push(1)),
+            Err(GenericError::NoValue)
+        ));
+    }
+
+    #[test]
+    fn test_type_check() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut conn = client.get_connection().unwrap();
+        redis::cmd("DEL")
+            .arg("test_type_check_generic:type")
+            .execute(&mut conn);
+
+        let s1 = Generic::<Vec<i32>>::with_type_check("test_type_check_generic", client.clone());
+        assert!(s1.is_ok());
+        let s2 = Generic::<Vec<i32>>::with_type_check("test_type_check_generic", client.clone());
+        assert!(s2.is_ok());
+        let s3 = Generic::<String>::with_type_check("test_type_check_generic", client);
+        assert!(matches!(s3, Err(GenericError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_wait_for() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut conn = client.get_connection().unwrap();
+        redis::cmd("CONFIG")
+            .arg("SET")
+            .arg("notify-keyspace-events")
+            .arg("K$")
+            .execute(&mut conn);
+
+        let mut s1 = Generic::with_value(1, "test_wait_for_generic", client.clone());
+        assert!(matches!(
+            s1.wait_for(2, Duration::from_millis(100)),
+            Err(GenericError::Timeout)
+        ));
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(Duration::from_millis(100));
+                Generic::with_value(2, "test_wait_for_generic", client.clone());
+            });
+            s1.wait_for(2, Duration::from_secs(1)).unwrap();
+        });
+        assert_eq!(s1.cached(), Some(&2));
+    }
+
+    #[test]
+    fn test_swap() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1 = Generic::with_value(vec![1], "test_swap_generic", client.clone());
+        let mut s2: Generic<Vec<i32>> = Generic::new("test_swap_generic2", client.clone());
+        s2.store(vec![2]);
+        s1.swap(&mut s2);
+        assert_eq!(s1, vec![2]);
+        assert_eq!(s2, vec![1]);
+
+        let mut s3: Generic<Vec<i32>> = Generic::new("test_swap_generic_empty", client.clone());
+        s3.swap(&mut s1);
+        assert_eq!(s3, vec![2]);
+        assert_eq!(s1.cached(), None);
+        assert_eq!(
+            Generic::<Vec<i32>>::with_load("test_swap_generic", client).cached(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let s1: Generic<Vec<i32>> = Generic::new("test_serialize_generic", client.clone());
+        assert_eq!(serde_json::to_string(&s1).unwrap(), "null");
+        let s2 = Generic::with_value(vec![1, 2], "test_serialize_generic", client);
+        assert_eq!(serde_json::to_string(&s2).unwrap(), "[1,2]");
+    }
+
+    #[test]
+    fn test_acquire_or() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1: Generic<Vec<i32>> = Generic::new("test_acquire_or_generic", client.clone());
+        s1.store(vec![1]);
+        assert_eq!(s1.acquire_or(vec![2]), &vec![1]);
+
+        let mut s2: Generic<Vec<i32>> = Generic::new("test_acquire_or_generic_empty", client);
+        assert_eq!(s2.acquire_or_else(|| vec![2]), &vec![2]);
+        assert_eq!(s2.try_get(s2.get_read_conn()), None);
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut conn = client.get_connection().unwrap();
+        redis::cmd("DEL")
+            .arg("test_get_or_insert_with_generic")
+            .execute(&mut conn);
+
+        let mut s1: Generic<Vec<i32>> =
+            Generic::new("test_get_or_insert_with_generic", client.clone());
+        assert_eq!(s1.get_or_insert_with(|| vec![1]), &vec![1]);
+        let mut s2: Generic<Vec<i32>> = Generic::new("test_get_or_insert_with_generic", client);
+        assert_eq!(
+            s2.get_or_insert_with(|| panic!("The value is stored already")),
+            &vec![1]
+        );
+    }
+
+    #[test]
+    fn test_cache_ttl() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1: Generic<i32> = Generic::with_cache_ttl(
+            Duration::from_millis(200),
+            "test_cache_ttl_generic",
+            client.clone(),
+        );
+        s1.store(1);
+        Generic::with_value(2, "test_cache_ttl_generic", client);
+        assert_eq!(s1.acquire(), &1);
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(s1.acquire(), &2);
+    }
+
+    #[test]
+    fn test_store_durable() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1: Generic<i32> = Generic::new("test_store_durable_generic", client.clone());
+        s1.store_durable(1, 0, Duration::from_millis(100)).unwrap();
+        // the test server has no replicas, so the write is never acknowledged by one
+        assert!(matches!(
+            s1.store_durable(2, 1, Duration::from_millis(100)),
+            Err(GenericError::NotReplicated {
+                acknowledged: 0,
+                required: 1
+            })
+        ));
+        assert_eq!(s1.cached(), Some(&2));
+        assert_eq!(
+            Generic::<i32>::with_load("test_store_durable_generic", client),
+            2
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut conn = client.get_connection().unwrap();
+        redis::cmd("DEL")
+            .arg("test_history_generic:history")
+            .execute(&mut conn);
+
+        let before = SystemTime::now() - Duration::from_secs(1);
+        let mut s1: Generic<i32> = Generic::with_history(3, "test_history_generic", client.clone());
+        for i in 1..=5 {
+            s1.store(i);
+        }
+        s1 += 1;
+
+        let history = s1.history(10);
+        let values: Vec<i32> = history.iter().map(|(_, v)| *v).collect();
+        assert_eq!(values, [4, 5, 6]);
+        assert!(history.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert!(history[0].0 >= before);
+        assert_eq!(s1.history(1)[0].1, 6);
+        assert!(s1.history(0).is_empty());
+
+        // a type without history mode does not append to it
+        let mut s2: Generic<i32> = Generic::new("test_history_generic", client);
+        s2.store(7);
+        assert_eq!(s2.history(10).len(), 3);
+    }
+
+    #[test]
+    fn test_history_writers() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut conn = client.get_connection().unwrap();
+        redis::cmd("DEL")
+            .arg("test_history_writers_generic1")
+            .arg("test_history_writers_generic1:history")
+            .arg("test_history_writers_generic2:history")
+            .execute(&mut conn);
+
+        let mut s1: Generic<i32> =
+            Generic::with_history(10, "test_history_writers_generic1", client.clone());
+        let mut s2: Generic<i32> =
+            Generic::with_history(10, "test_history_writers_generic2", client);
+        assert_eq!(*s1.get_or_insert_with(|| 1), 1);
+        s1.update(|v| *v += 1).unwrap();
+        s1.store_verified(3).unwrap();
+        s2.store(4);
+        s1.swap(&mut s2);
+
+        let values = |s: &Generic<i32>| {
+            s.history(10)
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(&s1), [1, 2, 3, 4]);
+        assert_eq!(values(&s2), [4, 3]);
+    }
+
+    #[test]
+    fn test_escape_pattern() {
+        assert_eq!(escape_pattern("plain:key"), "plain:key");
+        assert_eq!(escape_pattern("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\");
+    }
+
+    #[test]
+    fn test_store_verified() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1: Generic<i32> = Generic::new("test_store_verified_generic", client);
+        s1.store_verified(1).unwrap();
+        assert_eq!(s1.cached(), Some(&1));
+
+        // another instance overwrote the value between SET and GET
+        let written = s1.encode_value(&2);
+        let stored = s1.encode_value(&3);
+        assert!(matches!(
+            s1.verify(2, &written, Some(stored)),
+            Err(GenericError::VerifyFailed)
+        ));
+        assert_eq!(s1.cached(), Some(&3));
+
+        // the value was dropped between SET and GET
+        assert!(matches!(
+            s1.verify(2, &written, None),
+            Err(GenericError::VerifyFailed)
+        ));
+        assert_eq!(s1.cached(), None);
+    }
 }
@@ -1,7 +1,13 @@
+use crate::redis::metrics::metrics;
 use crate::redis::Generic;
+use redis::RedisResult;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::fmt::Display;
+use std::time::Instant;
+
+/// The default separator between the key of a value and the suffixes of its derived keys, e.g. `<key>:lock`.
+pub(crate) const DEFAULT_SEPARATOR: &str = ":";
 
 pub(crate) fn apply_operator<T>(mut me: Generic<T>, rhs: T, func: impl Fn(T, T) -> T) -> Generic<T>
 where
@@ -17,3 +23,110 @@ where
     me.store(value);
     me
 }
+
+pub(crate) fn apply_checked_operator<T>(
+    mut me: Generic<T>,
+    rhs: T,
+    func: impl Fn(T, T) -> Option<T>,
+) -> Option<Generic<T>>
+where
+    T: Copy + Serialize + DeserializeOwned,
+{
+    let value = match me.cache {
+        Some(value) => func(value, rhs)?,
+        None => rhs,
+    };
+
+    me.store(value);
+    Some(me)
+}
+
+/// Runs the redis operation in a span, which is tagged with the key and the name of the operation.
+/// If the operation fails, an error event is emitted.
+/// If [Metrics](crate::redis::Metrics) are registered, the latency of the operation is recorded.
+///
+/// Without the feature `tracing` and metrics, only the operation is executed.
+pub(crate) fn instrumented<R>(
+    op: &str,
+    key: &str,
+    f: impl FnOnce() -> RedisResult<R>,
+) -> RedisResult<R> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("redis", op, key).entered();
+
+    let res = match metrics() {
+        Some(metrics) => {
+            let start = Instant::now();
+            let res = f();
+            metrics.record(op, key, start.elapsed(), res.is_ok());
+            res
+        }
+        None => f(),
+    };
+
+    #[cfg(feature = "tracing")]
+    if let Err(err) = &res {
+        tracing::error!(%err, "Redis operation failed");
+    }
+    res
+}
+
+/// Awaits the redis operation like [instrumented] in a span, which is tagged with the key and the name of the operation.
+/// Only available with the feature `async`.
+#[cfg(feature = "async")]
+pub(crate) async fn instrumented_async<R>(
+    op: &str,
+    key: &str,
+    fut: impl std::future::Future<Output = RedisResult<R>>,
+) -> RedisResult<R> {
+    let inner = async {
+        let res = match metrics() {
+            Some(metrics) => {
+                let start = Instant::now();
+                let res = fut.await;
+                metrics.record(op, key, start.elapsed(), res.is_ok());
+                res
+            }
+            None => fut.await,
+        };
+
+        #[cfg(feature = "tracing")]
+        if let Err(err) = &res {
+            tracing::error!(%err, "Redis operation failed");
+        }
+        res
+    };
+
+    #[cfg(feature = "tracing")]
+    let inner = tracing::Instrument::instrument(inner, tracing::debug_span!("redis", op, key));
+    inner.await
+}
+
+/// Runs the release script of an async guard in a background task, if the guard is dropped without releasing it.
+/// Outside of a tokio runtime, nothing is done and the lock is kept until it expires.
+///
+/// The script gets the key, the uuid of the lock and the separator of the derived keys as arguments.
+/// It is instrumented as `unlock` like the release of the sync guards.
+#[cfg(feature = "async")]
+pub(crate) fn release_in_background(
+    mut conn: redis::aio::Connection,
+    script: &'static str,
+    key: String,
+    uuid: usize,
+    separator: String,
+) {
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        handle.spawn(async move {
+            let _: RedisResult<()> = instrumented_async(
+                "unlock",
+                &key,
+                redis::Script::new(script)
+                    .arg(&key)
+                    .arg(uuid)
+                    .arg(&separator)
+                    .invoke_async(&mut conn),
+            )
+            .await;
+        });
+    }
+}
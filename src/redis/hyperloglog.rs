@@ -0,0 +1,103 @@
+//! # HyperLogLog
+//! This module contains the HyperLogLog type for approximate distinct counting.
+use crate::redis::{instrumented, Connector};
+use redis::RedisResult;
+use serde::Serialize;
+
+/// A HyperLogLog that is stored in Redis.
+///
+/// It estimates the number of distinct items with a bounded memory of 12kB per key
+/// and a standard error of 0.81%, e.g. to count unique visitors across services.
+/// The items are serialized to JSON before they are added, so equal items are counted once.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::HyperLogLog;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut visitors = HyperLogLog::new("test_hll_example", client.clone());
+/// let mut other = HyperLogLog::new("test_hll_example2", client);
+/// visitors.clear();
+/// other.clear();
+/// visitors.add(&"alice");
+/// visitors.add(&"alice");
+/// other.add(&"bob");
+/// assert_eq!(visitors.count(), 1);
+/// visitors.merge(&[&other]);
+/// assert_eq!(visitors.count(), 2);
+/// ```
+pub struct HyperLogLog<T> {
+    key: String,
+    client: Connector,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> HyperLogLog<T>
+where
+    T: Serialize,
+{
+    /// Creates a new HyperLogLog.
+    /// Already added items in redis are kept.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Adds the item and returns true, if the estimated count changed.
+    pub fn add(&mut self, item: &T) -> bool {
+        let mut conn = self.client.get_connection().unwrap();
+        let item = serde_json::to_vec(item).expect("Failed to serialize item");
+        let res: RedisResult<bool> = instrumented("add", &self.key, || {
+            redis::Cmd::pfadd(&self.key, item).query(&mut conn)
+        });
+        res.expect("Failed to add item")
+    }
+
+    /// Returns the estimated number of distinct items.
+    pub fn count(&self) -> u64 {
+        // PFCOUNT may write its cache to the key, so it is not sent to a replica
+        let mut conn = self.client.get_connection().unwrap();
+        let res: RedisResult<u64> = instrumented("count", &self.key, || {
+            redis::Cmd::pfcount(&self.key).query(&mut conn)
+        });
+        res.expect("Failed to count items")
+    }
+
+    /// Merges the items of the others into this HyperLogLog.
+    pub fn merge(&mut self, others: &[&HyperLogLog<T>]) {
+        let mut conn = self.client.get_connection().unwrap();
+        let keys: Vec<&str> = others.iter().map(|o| o.key.as_str()).collect();
+        instrumented("merge", &self.key, || {
+            redis::Cmd::pfmerge(&self.key, keys).query::<()>(&mut conn)
+        })
+        .expect("Failed to merge");
+    }
+
+    /// Removes all items
+    pub fn clear(&self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("clear", &self.key, || {
+            redis::Cmd::del(&self.key).query::<()>(&mut conn)
+        })
+        .expect("Failed to clear HyperLogLog");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hyperloglog() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut hll = HyperLogLog::new("test_hyperloglog", client);
+        hll.clear();
+        for i in 0..100 {
+            hll.add(&(i % 10));
+        }
+        assert_eq!(hll.count(), 10);
+    }
+}
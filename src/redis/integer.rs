@@ -1,6 +1,43 @@
 //! The integer module contains the Ti32 struct which is a wrapper around an i32 value stored in Redis.
 
-use crate::redis::Generic;
+use crate::redis::{apply_checked_operator, apply_operator, instrumented, Generic};
+use std::time::Instant;
+
+/// The increment script.
+/// It is used to increment or decrement the value in Redis and to check the bounds of the type.
+/// If the new value is out of the bounds, the operation is reverted and an error is returned.
+/// Returns the new value.
+///
+/// Takes 5 Arguments:
+/// 1. The key of the value,
+/// 2. The command, INCRBY or DECRBY,
+/// 3. The amount,
+/// 4. The minimum of the type,
+/// 5. The maximum of the type.
+const INCREMENT_SCRIPT: &str = r#"
+local val = redis.call(ARGV[2], ARGV[1], ARGV[3])
+if val < tonumber(ARGV[4]) or val > tonumber(ARGV[5]) then
+    redis.call(ARGV[2] == "INCRBY" and "DECRBY" or "INCRBY", ARGV[1], ARGV[3])
+    return redis.error_reply("Value out of range")
+end
+return val"#;
+
+/// The saturating decrement script.
+/// It is used to decrement the value in Redis, but not below the minimum of the type.
+/// Returns the new value.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the value,
+/// 2. The amount,
+/// 3. The minimum of the type.
+const SATURATING_DECREMENT_SCRIPT: &str = r#"
+local val = redis.call("DECRBY", ARGV[1], ARGV[2])
+if val < tonumber(ARGV[3]) then
+    redis.call("SET", ARGV[1], ARGV[3])
+    return ARGV[3]
+end
+return val"#;
+
 pub type Tusize = Generic<usize>;
 pub type Tu8 = Generic<u8>;
 pub type Tu16 = Generic<u16>;
@@ -14,6 +51,123 @@ pub type Ti16 = Generic<i16>;
 pub type Ti32 = Generic<i32>;
 pub type Ti64 = Generic<i64>;
 
+/// Implements the overflow-safe arithmetic for the integer types.
+macro_rules! impl_overflow_ops {
+    ($($t:ty),*) => {
+        $(
+            impl Generic<$t> {
+                /// Checked integer addition. Stores the result and returns None, if an overflow occurred.
+                /// The stored value is not changed on overflow.
+                pub fn checked_add(self, rhs: $t) -> Option<Self> {
+                    apply_checked_operator(self, rhs, <$t>::checked_add)
+                }
+
+                /// Checked integer subtraction. Stores the result and returns None, if an overflow occurred.
+                /// The stored value is not changed on overflow.
+                pub fn checked_sub(self, rhs: $t) -> Option<Self> {
+                    apply_checked_operator(self, rhs, <$t>::checked_sub)
+                }
+
+                /// Checked integer multiplication. Stores the result and returns None, if an overflow occurred.
+                /// The stored value is not changed on overflow.
+                pub fn checked_mul(self, rhs: $t) -> Option<Self> {
+                    apply_checked_operator(self, rhs, <$t>::checked_mul)
+                }
+
+                /// Saturating integer addition. Stores the result, which is bounded by the numeric limits.
+                pub fn saturating_add(self, rhs: $t) -> Self {
+                    apply_operator(self, rhs, <$t>::saturating_add)
+                }
+
+                /// Saturating integer subtraction. Stores the result, which is bounded by the numeric limits.
+                /// The result is computed from the cache, so use [Self::saturating_decrement] for concurrent decrements.
+                pub fn saturating_sub(self, rhs: $t) -> Self {
+                    apply_operator(self, rhs, <$t>::saturating_sub)
+                }
+
+                /// Saturating integer multiplication. Stores the result, which is bounded by the numeric limits.
+                pub fn saturating_mul(self, rhs: $t) -> Self {
+                    apply_operator(self, rhs, <$t>::saturating_mul)
+                }
+
+                /// Increments the value in Redis with INCRBY and returns the new value.
+                /// In contrast to `+=`, the value is computed by Redis, so concurrent increments are not lost.
+                /// A missing value is treated as 0. The cache is updated with the new value.
+                ///
+                /// Only values stored without compression can be incremented.
+                ///
+                /// # Panics
+                ///
+                /// Panics if the new value overflows the type. The stored value is not changed then.
+                pub fn increment(&mut self, by: $t) -> $t {
+                    self.increment_by("INCRBY", by)
+                }
+
+                /// Decrements the value in Redis with DECRBY and returns the new value like [Self::increment].
+                pub fn decrement(&mut self, by: $t) -> $t {
+                    self.increment_by("DECRBY", by)
+                }
+
+                /// Decrements the value in Redis like [Self::decrement], but the value stops at the minimum of the type
+                /// instead of panicking, e.g. at 0 for the unsigned types. Returns the new value.
+                ///
+                /// The value is read, decremented and clamped in one script,
+                /// so concurrent decrements cannot drive a counter of available slots below zero.
+                ///
+                /// # Panics
+                ///
+                /// Redis stores integers as signed 64-bit numbers, so DECRBY fails and this function panics,
+                /// if the stored value or `by` is above `i64::MAX`, which is only possible for u64 and usize,
+                /// or if the decrement of an i64 goes below `i64::MIN`. The stored value is not changed then.
+                ///
+                /// # Example
+                ///
+                /// ```
+                /// use dtypes::redis::Du32 as u32;
+                ///
+                /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+                /// let mut slots = u32::with_value(2, "test_saturating_decrement_example", client);
+                /// assert_eq!(slots.saturating_decrement(1), 1);
+                /// assert_eq!(slots.saturating_decrement(5), 0);
+                /// ```
+                pub fn saturating_decrement(&mut self, by: $t) -> $t {
+                    let mut conn = self.get_conn();
+                    let value: $t = instrumented("saturating_decrement", &self.key, || {
+                        redis::Script::new(SATURATING_DECREMENT_SCRIPT)
+                            .arg(&self.key)
+                            .arg(by)
+                            .arg(<$t>::MIN)
+                            .invoke(&mut conn)
+                    })
+                    .expect("Failed to decrement value");
+                    self.cache = Some(value);
+                    self.fetched_at = Some(Instant::now());
+                    value
+                }
+
+                fn increment_by(&mut self, cmd: &str, by: $t) -> $t {
+                    let mut conn = self.get_conn();
+                    let value: $t = instrumented("increment", &self.key, || {
+                        redis::Script::new(INCREMENT_SCRIPT)
+                            .arg(&self.key)
+                            .arg(cmd)
+                            .arg(by)
+                            .arg(<$t>::MIN)
+                            .arg(<$t>::MAX)
+                            .invoke(&mut conn)
+                    })
+                    .expect("Failed to increment value");
+                    self.cache = Some(value);
+                    self.fetched_at = Some(Instant::now());
+                    value
+                }
+            }
+        )*
+    };
+}
+
+impl_overflow_ops!(usize, u8, u16, u32, u64, isize, i8, i16, i32, i64);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -27,6 +181,7 @@ mod tests {
         assert_eq!(i32, 3);
     }
 
+    #[allow(clippy::assign_op_pattern)]
     #[test]
     fn test_sub() {
         let client = redis::Client::open("redis://localhost:6379").unwrap();
@@ -51,6 +206,7 @@ mod tests {
         assert_eq!(i32, 0);
     }
 
+    #[allow(clippy::assign_op_pattern)]
     #[test]
     fn test_multiple_calculations() {
         let client = redis::Client::open("redis://localhost:6379").unwrap();
@@ -69,4 +225,58 @@ mod tests {
         i32 += Ti32::with_value(2, "test_add_assign2", client.clone());
         assert_eq!(i32, 3);
     }
+
+    #[test]
+    fn test_checked() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let u8 = Tu8::with_value(250, "test_checked", client.clone());
+        let u8 = u8.checked_add(5).unwrap();
+        assert_eq!(u8, 255);
+        assert!(u8.checked_add(1).is_none());
+        assert_eq!(Tu8::with_load("test_checked", client), 255);
+    }
+
+    #[test]
+    fn test_increment() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut u8 = Tu8::with_value(250, "test_increment", client.clone());
+        let mut other = Tu8::new("test_increment", client);
+        assert_eq!(u8.increment(5), 255);
+        assert_eq!(other.decrement(10), 245);
+        assert_eq!(u8.acquire(), &245);
+
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| u8.increment(11)));
+        assert!(res.is_err());
+        assert_eq!(other.acquire(), &245);
+    }
+
+    #[test]
+    fn test_saturating() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let u8 = Tu8::with_value(250, "test_saturating", client.clone());
+        let u8 = u8.saturating_add(10);
+        assert_eq!(u8, 255);
+        let u8 = u8.saturating_mul(2).saturating_sub(255).saturating_sub(1);
+        assert_eq!(u8, 0);
+    }
+
+    #[test]
+    fn test_saturating_decrement() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut slots = Tu32::with_value(10, "test_saturating_decrement", client.clone());
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let client = client.clone();
+                s.spawn(move || {
+                    let mut slots = Tu32::new("test_saturating_decrement", client);
+                    for _ in 0..5 {
+                        slots.saturating_decrement(1);
+                    }
+                });
+            }
+        });
+        // 20 decrements of 10 slots stop at zero
+        assert_eq!(slots.acquire(), &0);
+        assert_eq!(slots.saturating_decrement(1), 0);
+    }
 }
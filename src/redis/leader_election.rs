@@ -0,0 +1,190 @@
+//! # Leader Election
+//! This module contains the leader election type.
+use crate::redis::{instrumented, Connector, Error};
+use std::time::Duration;
+
+/// The campaign script.
+/// It is used to become the leader, if there is no leader at the moment.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the election,
+/// 2. The uuid of the candidate,
+/// 3. The lease time in milliseconds.
+const CAMPAIGN_SCRIPT: &str = r#"
+local val = redis.call("get", ARGV[1] .. ":leader")
+if val == false or val == ARGV[2] then
+    redis.call("set", ARGV[1] .. ":leader", ARGV[2], "px", ARGV[3])
+    return 1
+end
+return 0"#;
+
+/// The renew script.
+/// It is used to extend the lease, only if the candidate is still the leader.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the election,
+/// 2. The uuid of the candidate,
+/// 3. The lease time in milliseconds.
+const RENEW_SCRIPT: &str = r#"
+if redis.call("get", ARGV[1] .. ":leader") == ARGV[2] then
+    redis.call("pexpire", ARGV[1] .. ":leader", ARGV[3])
+    return 1
+end
+return 0"#;
+
+/// The resign script.
+/// It is used to drop the lease, only if the candidate is the leader.
+///
+/// Takes 2 Arguments:
+/// 1. The key of the election,
+/// 2. The uuid of the candidate.
+const RESIGN_SCRIPT: &str = r#"
+if redis.call("get", ARGV[1] .. ":leader") == ARGV[2] then
+    redis.call("del", ARGV[1] .. ":leader")
+    return 1
+end
+return 0"#;
+
+/// The uuid script.
+/// It is used to generate a unique identity for the candidate.
+///
+/// Takes 1 Argument:
+/// 1. The key of the election.
+const UUID_SCRIPT: &str = r#"
+return redis.call("INCR", ARGV[1] .. ":uuids")"#;
+
+/// A distributed leader election with a lease.
+///
+/// Only one instance can be the leader at a time. The leadership is a lease, which expires after
+/// the given time, if it is not renewed. So if the leader dies, another instance can take over.
+/// This can be used to build active/standby topologies of services.
+///
+/// The leadership is resigned, when the LeaderElection is dropped.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::LeaderElection;
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut active = LeaderElection::new("test_leader_example", Duration::from_secs(1), client.clone());
+/// let mut standby = LeaderElection::new("test_leader_example", Duration::from_secs(1), client);
+///
+/// assert!(active.campaign().unwrap());
+/// assert!(!standby.campaign().unwrap());
+/// assert!(active.renew().unwrap());
+/// active.resign().unwrap();
+/// assert!(standby.campaign().unwrap());
+/// assert!(standby.is_leader());
+/// ```
+pub struct LeaderElection {
+    key: String,
+    client: Connector,
+    lease: Duration,
+    uuid: usize,
+}
+
+impl LeaderElection {
+    /// Creates a new candidate for the election.
+    /// It does not campaign for the leadership.
+    pub fn new(key: &str, lease: Duration, client: impl Into<Connector>) -> Self {
+        let client = client.into();
+        let mut conn = client
+            .get_connection()
+            .expect("Failed to get connection to Redis");
+
+        let uuid = instrumented("uuid", key, || {
+            redis::Script::new(UUID_SCRIPT)
+                .arg(key)
+                .invoke::<usize>(&mut conn)
+        })
+        .expect("Failed to get uuid");
+
+        Self {
+            key: key.to_string(),
+            client,
+            lease,
+            uuid,
+        }
+    }
+
+    /// Tries to become the leader.
+    /// Returns true, if this instance is the leader now. This renews the lease, if it was already the leader.
+    /// Returns an [Error], if Redis cannot be reached.
+    pub fn campaign(&mut self) -> Result<bool, Error> {
+        self.invoke("campaign", CAMPAIGN_SCRIPT)
+    }
+
+    /// Extends the lease by the lease time from now on.
+    /// Returns false, if this instance is not the leader (anymore).
+    pub fn renew(&mut self) -> Result<bool, Error> {
+        self.invoke("renew", RENEW_SCRIPT)
+    }
+
+    /// Drops the leadership, so another instance can become the leader.
+    pub fn resign(&mut self) -> Result<(), Error> {
+        let mut conn = self.client.get_connection()?;
+        instrumented("resign", &self.key, || {
+            redis::Script::new(RESIGN_SCRIPT)
+                .arg(&self.key)
+                .arg(self.uuid)
+                .invoke::<()>(&mut conn)
+        })?;
+        Ok(())
+    }
+
+    /// Returns true, if this instance is the current leader.
+    pub fn is_leader(&self) -> bool {
+        self.leader() == Some(self.uuid)
+    }
+
+    /// Returns the uuid of the current leader, if there is one.
+    pub fn leader(&self) -> Option<usize> {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("leader", &self.key, || {
+            redis::Cmd::get(format!("{}:leader", self.key)).query(&mut conn)
+        })
+        .unwrap()
+    }
+
+    fn invoke(&self, op: &str, script: &str) -> Result<bool, Error> {
+        let mut conn = self.client.get_connection()?;
+        let res: i8 = instrumented(op, &self.key, || {
+            redis::Script::new(script)
+                .arg(&self.key)
+                .arg(self.uuid)
+                .arg(self.lease.as_millis() as u64)
+                .invoke(&mut conn)
+        })?;
+        Ok(res == 1)
+    }
+}
+
+impl Drop for LeaderElection {
+    /// Resigns the leadership. An error is ignored, because the lease expires anyway.
+    fn drop(&mut self) {
+        let _ = self.resign();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leader_election() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut first =
+            LeaderElection::new("test_leader", Duration::from_millis(200), client.clone());
+        let mut second = LeaderElection::new("test_leader", Duration::from_millis(200), client);
+        assert!(first.campaign().unwrap());
+        assert!(first.is_leader());
+        assert!(!second.campaign().unwrap());
+        assert!(!second.is_leader());
+        // the lease expires, if it is not renewed
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(!first.renew().unwrap());
+        assert!(second.campaign().unwrap());
+        assert_eq!(first.leader(), Some(second.uuid));
+    }
+}
@@ -1,8 +1,10 @@
-use crate::redis::Generic;
+use crate::redis::compression::{decode, encode};
+use crate::redis::{instrumented, Compression, Connector};
+use redis::{FromRedisValue, RedisResult};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::VecDeque;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Index};
 
 /// A list that is stored in Redis.
 ///
@@ -18,9 +20,30 @@ use std::ops::{Deref, DerefMut};
 /// assert_eq!(list.pop_front(), Some(1));
 /// list.clear();
 /// ```
+///
+/// The list can be cloned cheaply, so multiple threads can push to the same list.
+///
+/// ```
+/// use dtypes::redis::List;
+/// use std::thread;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let list = List::new("test_list_clone", client);
+/// list.clear();
+/// thread::scope(|s| {
+///     for i in 0..4 {
+///         let mut list = list.clone();
+///         s.spawn(move || list.push_back(&i));
+///     }
+/// });
+/// assert_eq!(list.len(), 4);
+/// list.clear();
+/// ```
 pub struct List<T> {
     key: String,
-    client: redis::Client,
+    client: Connector,
+    compression: Compression,
+    raw: Option<RawCodec<T>>,
     _conn: Option<redis::Connection>,
     _phantom: std::marker::PhantomData<T>,
 }
@@ -33,72 +56,431 @@ where
     ///
     /// There is no `with_value` method like (Generic::with_value)[crate::redis::Generic::with_value] because it is not possible to
     /// provide a good default behaviour in redis. So you have to think about, how you want to handle
-    /// already stored values in redis.
+    /// already stored values in redis. If the stored values should be replaced, use [List::from_vec].
     /// If you want a small performance boost, look at [ListCache].
-    pub fn new(key: &str, client: redis::Client) -> Self {
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
         Self {
-            client,
+            client: client.into(),
             key: key.to_string(),
+            compression: Compression::Raw,
+            raw: None,
             _conn: None,
             _phantom: Default::default(),
         }
     }
 
+    /// Creates a new List, which contains exactly the given values.
+    /// Already stored values in redis are removed.
+    ///
+    /// The old values are deleted and the new ones are pushed with a single RPUSH in one atomic pipeline,
+    /// so no other instance sees a partially seeded list.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_from_vec", client.clone());
+    /// list.push_back(&0);
+    /// let list = List::from_vec("test_list_from_vec", client, vec![1, 2, 3]);
+    /// assert_eq!(list.iter().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// list.clear();
+    /// ```
+    pub fn from_vec(key: &str, client: impl Into<Connector>, values: Vec<T>) -> Self {
+        let list = Self::new(key, client);
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(&list.key).ignore();
+        if !values.is_empty() {
+            let vals: Vec<Vec<u8>> = values.iter().map(|v| list.encode_value(v)).collect();
+            pipe.rpush(&list.key, vals).ignore();
+        }
+        pipe.incr(list.version_key(), 1).ignore();
+
+        let mut conn = list.client.get_connection().unwrap();
+        instrumented("from_vec", &list.key, || pipe.query::<()>(&mut conn))
+            .expect("Failed to seed list");
+        list
+    }
+
+    /// Creates a new List like [List::new], which compresses all values with the given algorithm.
+    pub fn with_compression(
+        compression: Compression,
+        key: &str,
+        client: impl Into<Connector>,
+    ) -> Self {
+        let mut list = Self::new(key, client);
+        list.compression = compression;
+        list
+    }
+
     /// Returns an iterator over the list.
-    pub fn iter(&self) -> ListIter<T> {
+    /// If a replica is configured in the [Connector], the values are read from the replica.
+    ///
+    /// The length is loaded once, so the iterator knows its exact size and can iterate from both ends.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_iter", client);
+    /// list.clear();
+    /// list.push_back_all(&[1, 2, 3]);
+    /// let mut iter = list.iter();
+    /// assert_eq!(iter.len(), 3);
+    /// assert_eq!(iter.next_back(), Some(3));
+    /// assert_eq!(iter.collect::<Vec<i32>>(), vec![1, 2]);
+    /// list.clear();
+    /// ```
+    pub fn iter(&self) -> ListIter<'_, T> {
         let len = self.len();
         ListIter {
             list: self,
             index: 0,
-            len,
+            end: len as isize,
         }
     }
 
-    /// Add a value to the front of the list
-    pub fn push_front(&mut self, val: &T) {
-        let mut conn = self.client.get_connection().unwrap();
-        redis::Cmd::lpush(
-            &self.key,
-            serde_json::to_string(val).expect("Failed to serialize value"),
-        )
-        .execute(&mut conn);
+    /// Add a value to the front of the list.
+    /// Returns the length of the list after the push, which is returned by LPUSH without an additional round trip.
+    pub fn push_front(&mut self, val: &T) -> usize {
+        let cmd = redis::Cmd::lpush(&self.key, self.encode_value(val));
+        self.mutate("push_front", cmd)
+            .expect("Failed to push value")
     }
 
-    /// Add a value to the back of the list
-    pub fn push_back(&mut self, val: &T) {
-        let mut conn = self.client.get_connection().unwrap();
-        redis::Cmd::rpush(
-            &self.key,
-            serde_json::to_string(val).expect("Failed to serialize value"),
-        )
-        .execute(&mut conn);
+    /// Add a value to the back of the list.
+    /// Returns the length of the list after the push, which is returned by RPUSH without an additional round trip.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_push_len", client);
+    /// list.clear();
+    /// assert_eq!(list.push_back(&1), 1);
+    /// assert_eq!(list.push_front(&0), 2);
+    /// assert_eq!(list.push_back_all(&[2, 3]), 4);
+    /// list.clear();
+    /// ```
+    pub fn push_back(&mut self, val: &T) -> usize {
+        let cmd = redis::Cmd::rpush(&self.key, self.encode_value(val));
+        self.mutate("push_back", cmd).expect("Failed to push value")
+    }
+
+    /// Add all values to the front of the list with a single LPUSH.
+    ///
+    /// The values are pushed one after another, so the last value of the slice ends up at the front,
+    /// like calling [List::push_front] for every value.
+    ///
+    /// Returns the length of the list after the push.
+    pub fn push_front_all(&mut self, vals: &[T]) -> usize {
+        if vals.is_empty() {
+            return self.len();
+        }
+        let vals: Vec<Vec<u8>> = vals.iter().map(|v| self.encode_value(v)).collect();
+        let cmd = redis::Cmd::lpush(&self.key, vals);
+        self.mutate("push_front_all", cmd)
+            .expect("Failed to push values")
+    }
+
+    /// Add all values to the back of the list with a single RPUSH.
+    /// Returns the length of the list after the push.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_push_all", client);
+    /// list.clear();
+    /// list.push_back_all(&[1, 2, 3]);
+    /// list.push_front_all(&[0, -1]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![-1, 0, 1, 2, 3]);
+    /// ```
+    pub fn push_back_all(&mut self, vals: &[T]) -> usize {
+        if vals.is_empty() {
+            return self.len();
+        }
+        let vals: Vec<Vec<u8>> = vals.iter().map(|v| self.encode_value(v)).collect();
+        let cmd = redis::Cmd::rpush(&self.key, vals);
+        self.mutate("push_back_all", cmd)
+            .expect("Failed to push values")
+    }
+
+    /// Inserts the value before the first occurrence of the pivot.
+    /// Returns false, if the pivot was not found.
+    ///
+    /// The values are compared by their serialized form.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_insert", client);
+    /// list.clear();
+    /// list.push_back(&1);
+    /// list.push_back(&3);
+    /// assert!(list.insert_before(&3, &2));
+    /// assert!(list.insert_after(&3, &4));
+    /// assert!(!list.insert_after(&5, &6));
+    /// assert_eq!(list.iter().collect::<Vec<i32>>(), vec![1, 2, 3, 4]);
+    /// list.clear();
+    /// ```
+    pub fn insert_before(&mut self, pivot: &T, val: &T) -> bool {
+        let cmd =
+            redis::Cmd::linsert_before(&self.key, self.encode_value(pivot), self.encode_value(val));
+        let res: isize = self
+            .mutate("insert_before", cmd)
+            .expect("Failed to insert value");
+        res > 0
+    }
+
+    /// Inserts the value after the first occurrence of the pivot.
+    /// Returns false, if the pivot was not found.
+    ///
+    /// The values are compared by their serialized form.
+    pub fn insert_after(&mut self, pivot: &T, val: &T) -> bool {
+        let cmd =
+            redis::Cmd::linsert_after(&self.key, self.encode_value(pivot), self.encode_value(val));
+        let res: isize = self
+            .mutate("insert_after", cmd)
+            .expect("Failed to insert value");
+        res > 0
     }
 
     /// Removes and returns the first value of the list
     pub fn pop_front(&mut self) -> Option<T> {
-        let mut conn = self.client.get_connection().unwrap();
-        let val: Option<String> = redis::Cmd::lpop(&self.key, None).query(&mut conn).ok();
-        val.map(|v| serde_json::from_str(&v).expect("Failed to deserialize value"))
+        let val: Option<Vec<u8>> = self
+            .mutate("pop_front", redis::Cmd::lpop(&self.key, None))
+            .ok()
+            .flatten();
+        val.map(|v| self.decode_value(&v))
     }
 
     /// Removes and returns the last value of the list
     pub fn pop_back(&mut self) -> Option<T> {
+        let val: Option<Vec<u8>> = self
+            .mutate("pop_back", redis::Cmd::rpop(&self.key, None))
+            .ok()
+            .flatten();
+        val.map(|v| self.decode_value(&v))
+    }
+
+    /// Converts the list into an endless stream, which pops the values from the back of the list.
+    /// The stream waits with BRPOP until a value is pushed, so it can be used for consumer loops.
+    /// If you push to the front, the values are consumed in FIFO order.
+    ///
+    /// The stream ends, if the connection to Redis fails.
+    ///
+    /// Only available with the feature `async`.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    /// use futures_util::StreamExt;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_pop_stream", client);
+    /// list.clear();
+    /// list.push_front(&1);
+    /// list.push_front(&2);
+    /// let mut stream = Box::pin(list.into_pop_stream());
+    /// assert_eq!(stream.next().await, Some(1));
+    /// assert_eq!(stream.next().await, Some(2));
+    /// # });
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn into_pop_stream(self) -> impl futures_util::Stream<Item = T> {
+        futures_util::stream::unfold((self, None), |(list, conn)| async move {
+            let mut conn = match conn {
+                Some(conn) => conn,
+                None => list.client.get_async_connection().await.ok()?,
+            };
+
+            loop {
+                let res: Option<(String, Vec<u8>)> = redis::cmd("BRPOP")
+                    .arg(&list.key)
+                    .arg(0)
+                    .query_async(&mut conn)
+                    .await
+                    .ok()?;
+                if let Some((_, v)) = res {
+                    redis::Cmd::incr(list.version_key(), 1)
+                        .query_async::<_, ()>(&mut conn)
+                        .await
+                        .ok()?;
+                    return Some((list.decode_value(&v), (list, Some(conn))));
+                }
+            }
+        })
+    }
+
+    /// Returns the first value of the list without removing it
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_peek", client);
+    /// list.clear();
+    /// assert_eq!(list.front(), None);
+    /// list.push_back(&1);
+    /// list.push_back(&2);
+    /// assert_eq!(list.front(), Some(1));
+    /// assert_eq!(list.back(), Some(2));
+    /// assert_eq!(list.len(), 2);
+    /// list.clear();
+    /// ```
+    pub fn front(&self) -> Option<T> {
+        self.peek("front", 0)
+    }
+
+    /// Returns the last value of the list without removing it
+    pub fn back(&self) -> Option<T> {
+        self.peek("back", -1)
+    }
+
+    fn peek(&self, op: &str, index: isize) -> Option<T> {
         let mut conn = self.client.get_connection().unwrap();
-        let val: Option<String> = redis::Cmd::rpop(&self.key, None).query(&mut conn).ok();
-        val.map(|v| serde_json::from_str(&v).expect("Failed to deserialize value"))
+        let val: Option<Vec<u8>> = instrumented(op, &self.key, || {
+            redis::Cmd::lindex(&self.key, index).query(&mut conn)
+        })
+        .expect("Failed to peek value");
+        val.map(|v| self.decode_value(&v))
     }
 
-    /// Returns the length of the list
+    /// Returns the length of the list.
+    /// If a replica is configured in the [Connector], it is read from the replica.
     pub fn len(&self) -> usize {
-        let mut conn = self.client.get_connection().unwrap();
-        let len: usize = redis::Cmd::llen(&self.key).query(&mut conn).unwrap();
+        let mut conn = self.client.get_read_connection().unwrap();
+        let len: usize = instrumented("len", &self.key, || {
+            redis::Cmd::llen(&self.key).query(&mut conn)
+        })
+        .unwrap();
         len
     }
 
-    /// Removes all values from the list
+    /// Trims the list, so it only contains the values between start and stop (both inclusive).
+    /// Negative indices count from the end of the list like in Redis, so -1 is the last value.
+    pub fn trim(&mut self, start: isize, stop: isize) {
+        self.mutate::<()>("trim", redis::Cmd::ltrim(&self.key, start, stop))
+            .expect("Failed to trim list");
+    }
+
+    /// Trims the list, so it only keeps the newest `max_len` values at the back.
+    /// This can be used to implement a ring buffer, if you push your values to the back.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_cap_back", client);
+    /// list.clear();
+    /// for i in 0..10 {
+    ///     list.push_back(&i);
+    ///     list.cap_back(3);
+    /// }
+    /// assert_eq!(list.iter().collect::<Vec<i32>>(), vec![7, 8, 9]);
+    /// list.clear();
+    /// ```
+    pub fn cap_back(&mut self, max_len: usize) {
+        if max_len == 0 {
+            self.clear();
+            return;
+        }
+        self.trim(-(max_len as isize), -1);
+    }
+
+    /// Removes the occurrences of the value from the list and returns the number of removed values.
+    ///
+    /// The count limits the number of removed values:
+    /// * count > 0: Removes values from the front to the back.
+    /// * count < 0: Removes values from the back to the front.
+    /// * count = 0: Removes all values.
+    ///
+    /// The values are compared by their serialized form.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_remove", client);
+    /// list.clear();
+    /// for i in [1, 2, 1, 3, 1] {
+    ///     list.push_back(&i);
+    /// }
+    /// assert_eq!(list.remove(&1, -1), 1);
+    /// assert_eq!(list.iter().collect::<Vec<i32>>(), vec![1, 2, 1, 3]);
+    /// assert_eq!(list.remove(&1, 0), 2);
+    /// assert_eq!(list.iter().collect::<Vec<i32>>(), vec![2, 3]);
+    /// list.clear();
+    /// ```
+    pub fn remove(&mut self, val: &T, count: isize) -> usize {
+        let cmd = redis::Cmd::lrem(&self.key, count, self.encode_value(val));
+        self.mutate("remove", cmd).expect("Failed to remove value")
+    }
+
+    /// Removes all values from the list and returns them in order.
+    ///
+    /// The values are loaded and the list is deleted in one transaction,
+    /// so no value, which is pushed concurrently, is lost between both steps.
+    /// The version of the list is removed like in [List::clear].
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_drain", client);
+    /// list.clear();
+    /// list.push_back_all(&[1, 2, 3]);
+    /// assert_eq!(list.drain(), vec![1, 2, 3]);
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> Vec<T> {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: RedisResult<(Vec<Vec<u8>>,)> = instrumented("drain", &self.key, || {
+            redis::pipe()
+                .atomic()
+                .lrange(&self.key, 0, -1)
+                .del(&self.key)
+                .ignore()
+                .del(self.version_key())
+                .ignore()
+                .query(&mut conn)
+        });
+        let (vals,) = res.expect("Failed to drain list");
+        vals.into_iter().map(|v| self.decode_value(&v)).collect()
+    }
+
+    /// Removes all values from the list.
+    /// The version of the list is removed too, so no key is left in Redis.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    /// use redis::Commands;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_clear", client.clone());
+    /// list.push_back(&1);
+    /// list.clear();
+    /// let mut conn = client.get_connection().unwrap();
+    /// assert!(!conn.exists::<_, bool>("test_list_clear:version").unwrap());
+    /// ```
     pub fn clear(&self) {
         let mut conn = self.client.get_connection().unwrap();
-        redis::Cmd::del(&self.key).execute(&mut conn);
+        instrumented("clear", &self.key, || {
+            redis::Cmd::del(&[self.key.clone(), self.version_key()]).query::<()>(&mut conn)
+        })
+        .expect("Failed to clear list");
     }
 
     /// Returns true if the list contains the value
@@ -106,28 +488,291 @@ where
     where
         T: PartialEq,
     {
-        let mut conn = self.client.get_connection().unwrap();
-        let val: Option<String> = redis::Cmd::lrange(&self.key, 0, -1)
-            .query(&mut conn)
-            .ok()
-            .and_then(|v: Vec<String>| {
-                v.into_iter()
-                    .find(|v| serde_json::from_str::<T>(v).unwrap() == *val)
-            });
+        let mut conn = self.client.get_read_connection().unwrap();
+        let val: Option<Vec<u8>> = instrumented("contains", &self.key, || {
+            redis::Cmd::lrange(&self.key, 0, -1).query(&mut conn)
+        })
+        .ok()
+        .and_then(|v: Vec<Vec<u8>>| v.into_iter().find(|v| self.decode_value(v) == *val));
         val.is_some()
     }
 
+    /// Returns up to limit values starting at the offset with a single LRANGE.
+    /// If a replica is configured in the [Connector], the values are read from the replica.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_page", client);
+    /// list.clear();
+    /// list.push_back_all(&[1, 2, 3, 4, 5]);
+    /// assert_eq!(list.page(1, 2), vec![2, 3]);
+    /// assert_eq!(list.page(4, 2), vec![5]);
+    /// assert_eq!(list.pages(2).collect::<Vec<_>>(), vec![vec![1, 2], vec![3, 4], vec![5]]);
+    /// list.clear();
+    /// ```
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<T> {
+        if limit == 0 {
+            return Vec::new();
+        }
+
+        let mut conn = self.client.get_read_connection().unwrap();
+        let start = offset as isize;
+        let vals: Vec<Vec<u8>> = instrumented("page", &self.key, || {
+            redis::Cmd::lrange(&self.key, start, start + limit as isize - 1).query(&mut conn)
+        })
+        .expect("Failed to load values");
+        vals.into_iter().map(|v| self.decode_value(&v)).collect()
+    }
+
+    /// Returns an iterator over the list, which yields the values in pages of the given size.
+    /// Every page is loaded with [List::page], so there is one round trip per page instead of per value.
+    ///
+    /// Values pushed or popped concurrently between two pages can be missed or returned twice.
+    ///
+    /// # Panics
+    /// Panics, if the size is zero.
+    pub fn pages(&self, size: usize) -> ListPages<'_, T> {
+        assert!(size > 0, "The page size must be greater than zero");
+        ListPages {
+            list: self,
+            offset: 0,
+            size,
+            done: false,
+        }
+    }
+
+    /// Returns the index of the first occurrence of the value with LPOS.
+    /// Returns None, if the list does not contain the value.
+    ///
+    /// The values are compared by their serialized form in Redis, so the list is not loaded.
+    /// If a replica is configured in the [Connector], the index is looked up on the replica.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_position", client);
+    /// list.clear();
+    /// list.push_back_all(&[1, 2, 3, 2]);
+    /// assert_eq!(list.position(&2), Some(1));
+    /// assert_eq!(list.position(&4), None);
+    /// assert_eq!(list.positions(&2), vec![1, 3]);
+    /// list.clear();
+    /// ```
+    pub fn position(&self, val: &T) -> Option<usize> {
+        let mut conn = self.client.get_read_connection().unwrap();
+        instrumented("position", &self.key, || {
+            redis::cmd("LPOS")
+                .arg(&self.key)
+                .arg(self.encode_value(val))
+                .query(&mut conn)
+        })
+        .expect("Failed to find value")
+    }
+
+    /// Returns the indices of all occurrences of the value with LPOS and COUNT 0 in ascending order.
+    ///
+    /// The values are compared by their serialized form in Redis like [List::position].
+    pub fn positions(&self, val: &T) -> Vec<usize> {
+        let mut conn = self.client.get_read_connection().unwrap();
+        instrumented("positions", &self.key, || {
+            redis::cmd("LPOS")
+                .arg(&self.key)
+                .arg(self.encode_value(val))
+                .arg("COUNT")
+                .arg(0)
+                .query(&mut conn)
+        })
+        .expect("Failed to find values")
+    }
+
+    /// Folds all values of the list from the front to the back.
+    /// The values are loaded with LRANGE in batches of 100 values, so there is no round trip per value.
+    /// If a replica is configured in the [Connector], the values are read from the replica.
+    ///
+    /// Values pushed or popped concurrently between two batches can be missed or folded twice.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::new("test_list_fold", client);
+    /// list.clear();
+    /// list.push_back_all(&[1, 2, 3]);
+    /// assert_eq!(list.fold(0, |acc, v| acc.max(v)), 3);
+    /// assert_eq!(list.sum(), 6);
+    /// list.clear();
+    /// ```
+    pub fn fold<A>(&self, init: A, f: impl Fn(A, T) -> A) -> A {
+        const BATCH_SIZE: isize = 100;
+
+        let mut conn = self.client.get_read_connection().unwrap();
+        let mut acc = init;
+        let mut start = 0;
+        loop {
+            let vals: Vec<Vec<u8>> = instrumented("fold", &self.key, || {
+                redis::Cmd::lrange(&self.key, start, start + BATCH_SIZE - 1).query(&mut conn)
+            })
+            .expect("Failed to load values");
+            let len = vals.len() as isize;
+            acc = vals
+                .into_iter()
+                .map(|v| self.decode_value(&v))
+                .fold(acc, &f);
+            if len < BATCH_SIZE {
+                return acc;
+            }
+            start += BATCH_SIZE;
+        }
+    }
+
+    /// Sums all values of the list like [Iterator::sum], but loads them in batches like [List::fold].
+    pub fn sum(&self) -> T
+    where
+        T: std::iter::Sum + std::ops::Add<Output = T>,
+    {
+        self.fold(std::iter::empty().sum(), |acc, v| acc + v)
+    }
+
     /// Returns true if the list is empty
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Encodes the value as raw bytes for a list created with [List::with_bytes],
+    /// otherwise as JSON with the configured compression.
+    pub(crate) fn encode_value(&self, value: &T) -> Vec<u8> {
+        match self.raw {
+            Some(raw) => (raw.encode)(value),
+            None => encode(value, self.compression),
+        }
+    }
+
+    /// Decodes the bytes like they were encoded by [List::encode_value].
+    pub(crate) fn decode_value(&self, bytes: &[u8]) -> T {
+        match self.raw {
+            Some(raw) => (raw.decode)(bytes),
+            None => decode(bytes),
+        }
+    }
+}
+
+impl<T> List<T> {
+    /// The key of the version, which is incremented on every manipulation of the list.
+    fn version_key(&self) -> String {
+        format!("{}:version", self.key)
+    }
+
+    /// Executes the manipulating command and increments the version in the same transaction,
+    /// so a [ListCache] can detect, that the list was modified.
+    ///
+    /// A List does not know, if a ListCache of another instance watches it, so the version is incremented on every write.
+    /// This costs an additional INCR and a MULTI/EXEC around the command, but still only one round trip.
+    fn mutate<R: FromRedisValue>(&self, op: &str, cmd: redis::Cmd) -> RedisResult<R> {
+        let mut conn = self.client.get_connection()?;
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .add_command(cmd)
+            .incr(self.version_key(), 1)
+            .ignore();
+        instrumented(op, &self.key, || pipe.query::<(R,)>(&mut conn)).map(|(res,)| res)
+    }
+
+    /// Returns the version of the list, which is 0 if the list was never modified.
+    fn version(&self) -> u64 {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: RedisResult<Option<u64>> = instrumented("version", &self.key, || {
+            redis::Cmd::get(self.version_key()).query(&mut conn)
+        });
+        res.expect("Failed to get version").unwrap_or(0)
+    }
+}
+
+/// Converts the values of a list from and to the stored bytes without serialization.
+/// It is only created by [List::with_bytes], where the values are known to be byte buffers.
+struct RawCodec<T> {
+    encode: fn(&T) -> Vec<u8>,
+    decode: fn(&[u8]) -> T,
+}
+
+impl<T> Clone for RawCodec<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RawCodec<T> {}
+
+impl List<Vec<u8>> {
+    /// Creates a new List like [List::new], which stores the byte buffers as they are instead of JSON.
+    ///
+    /// A JSON array needs up to four bytes per byte, so this saves memory for binary payloads like frames or chunks.
+    /// The values can be read by any other client as raw bytes, but not by a list created with [List::new].
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::List;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = List::with_bytes("test_list_bytes_example", client.clone());
+    /// list.clear();
+    /// list.push_back(&vec![0, 1, 255]);
+    /// list.cap_back(10);
+    ///
+    /// let mut conn = client.get_connection().unwrap();
+    /// let raw: Vec<u8> = redis::Cmd::lindex("test_list_bytes_example", 0).query(&mut conn).unwrap();
+    /// assert_eq!(raw, vec![0, 1, 255]);
+    /// assert_eq!(list.pop_front(), Some(vec![0, 1, 255]));
+    /// ```
+    pub fn with_bytes(key: &str, client: impl Into<Connector>) -> Self {
+        let mut list = Self::new(key, client);
+        list.raw = Some(RawCodec {
+            encode: Vec::clone,
+            decode: <[u8]>::to_vec,
+        });
+        list
+    }
+}
+
+impl<T> Clone for List<T> {
+    /// Creates a new handle to the same list in Redis.
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            client: self.client.clone(),
+            compression: self.compression,
+            raw: self.raw,
+            _conn: None,
+            _phantom: Default::default(),
+        }
+    }
 }
 
 /// An iterator over the list.
 pub struct ListIter<'a, T> {
     list: &'a List<T>,
     index: isize,
-    len: usize,
+    end: isize,
+}
+
+impl<'a, T> ListIter<'a, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn get(&self, index: isize) -> Option<T> {
+        let mut conn = self.list.client.get_read_connection().unwrap();
+        let val: Option<Vec<u8>> = instrumented("iter", &self.list.key, || {
+            redis::Cmd::lindex(&self.list.key, index).query(&mut conn)
+        })
+        .ok()
+        .flatten();
+        val.map(|v| self.list.decode_value(&v))
+    }
 }
 
 impl<'a, T> Iterator for ListIter<'a, T>
@@ -137,16 +782,64 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.len as isize {
+        if self.index >= self.end {
             return None;
         }
 
-        let mut conn = self.list.client.get_connection().unwrap();
-        let val: Option<String> = redis::Cmd::lindex(&self.list.key, self.index)
-            .query(&mut conn)
-            .ok();
+        let val = self.get(self.index);
         self.index += 1;
-        val.map(|v| serde_json::from_str(&v).expect("Failed to deserialize value"))
+        val
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.end - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for ListIter<'a, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        self.get(self.end)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ListIter<'a, T> where T: Serialize + DeserializeOwned {}
+
+/// An iterator over the pages of the list. See [List::pages].
+pub struct ListPages<'a, T> {
+    list: &'a List<T>,
+    offset: usize,
+    size: usize,
+    done: bool,
+}
+
+impl<'a, T> Iterator for ListPages<'a, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let page = self.list.page(self.offset, self.size);
+        self.offset += self.size;
+        // a page, which is not full, is the last one
+        self.done = page.len() < self.size;
+        if page.is_empty() {
+            return None;
+        }
+        Some(page)
     }
 }
 
@@ -155,6 +848,12 @@ where
 ///
 /// All manipulations are done on the cache and synced with the redis server.
 ///
+/// Every manipulation of a [List] or ListCache increments a version in Redis.
+/// So [ListCache::is_stale] can detect cheaply, if another instance modified the list,
+/// and [ListCache::pull_if_stale] loads it again only if needed.
+/// Manipulations through the methods of the underlying [List] are not applied to the cache,
+/// so they mark the cache as stale, too.
+///
 /// # Example
 /// ```
 /// use dtypes::redis::{ListCache, Mutex};
@@ -164,12 +863,24 @@ where
 /// list.push_back(1);
 /// list.push_back(2);
 /// assert_eq!(list.len(), 2);
+/// assert_eq!(list[1], 2);
 /// assert_eq!(list.pop_front(), Some(1));
 /// list.clear();
 /// ```
 pub struct ListCache<T> {
     list: List<T>,
     cache: VecDeque<T>,
+    ops: Option<Vec<ListOp>>,
+    version: u64,
+}
+
+/// A manipulation of the list, which is not synced with the redis server yet.
+enum ListOp {
+    PushBack(Vec<u8>),
+    PushFront(Vec<u8>),
+    PopBack,
+    PopFront,
+    Remove(Vec<u8>, isize),
 }
 
 impl<T> ListCache<T>
@@ -179,52 +890,178 @@ where
     /// Creates a new ListCache
     /// The list is loaded from the redis server.
     /// If you want to create an empty list, use [ListCache::without_load]
-    pub fn new(key: &str, client: redis::Client) -> Self {
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
         let mut s = Self::without_load(key, client);
         s.pull();
         s
     }
 
     /// Creates a new ListCache without loading the list from the redis server.
-    pub fn without_load(key: &str, client: redis::Client) -> Self {
+    pub fn without_load(key: &str, client: impl Into<Connector>) -> Self {
         let list = List::new(key, client);
         let val = VecDeque::new();
-        Self { list, cache: val }
+        Self {
+            list,
+            cache: val,
+            ops: None,
+            version: 0,
+        }
     }
 
+    /// Defers the sync of all following manipulations with the redis server.
+    /// They are collected locally and will be sent in one pipeline on [ListCache::flush] or drop.
+    /// This is useful for bulk loading, because it saves a lot of round trips.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::{List, ListCache};
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = ListCache::without_load("test_list_defer_sync", client.clone());
+    /// list.clear();
+    /// list.defer_sync();
+    /// for i in 0..1000 {
+    ///     list.push_back(i);
+    /// }
+    /// assert_eq!(List::<i32>::new("test_list_defer_sync", client.clone()).len(), 0);
+    /// list.flush();
+    /// assert_eq!(List::<i32>::new("test_list_defer_sync", client).len(), 1000);
+    /// list.clear();
+    /// ```
+    pub fn defer_sync(&mut self) {
+        if self.ops.is_none() {
+            self.ops = Some(Vec::new());
+        }
+    }
+
+    /// Loads the list from the redis server and overwrites the cache.
+    /// Deferred manipulations are flushed before.
     pub fn pull(&mut self) {
+        self.flush();
         let mut conn = self.list.client.get_connection().unwrap();
-        let val: VecDeque<T> = redis::Cmd::lrange(&self.list.key, 0, -1)
-            .query(&mut conn)
-            .ok()
-            .and_then(|v: Vec<String>| {
-                Option::from({
-                    v.into_iter()
-                        .map(|v| serde_json::from_str::<T>(&v).unwrap())
-                        .collect::<VecDeque<T>>()
-                })
-            })
-            .unwrap_or_default();
+        let res: RedisResult<(Vec<Vec<u8>>, Option<u64>)> =
+            instrumented("pull", &self.list.key, || {
+                redis::pipe()
+                    .atomic()
+                    .lrange(&self.list.key, 0, -1)
+                    .get(self.list.version_key())
+                    .query(&mut conn)
+            });
+        let (val, version) = res.unwrap_or_default();
         self.cache = val
+            .into_iter()
+            .map(|v| self.list.decode_value(&v))
+            .collect();
+        self.version = version.unwrap_or(0);
+    }
+
+    /// Returns true, if the list was modified in Redis since the last pull, apart from this cache.
+    /// Only the version is loaded, so it is cheap.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::{List, ListCache};
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut cache = ListCache::new("test_list_stale", client.clone());
+    /// cache.push_back(1);
+    /// assert!(!cache.is_stale());
+    /// List::new("test_list_stale", client).push_back(&2);
+    /// assert!(cache.is_stale());
+    /// assert!(cache.pull_if_stale());
+    /// assert_eq!(cache.len(), 2);
+    /// cache.clear();
+    /// ```
+    pub fn is_stale(&self) -> bool {
+        self.list.version() != self.version
+    }
+
+    /// Loads the list from the redis server, if the cache is stale.
+    /// Returns true, if the list was loaded.
+    pub fn pull_if_stale(&mut self) -> bool {
+        self.flush();
+        if !self.is_stale() {
+            return false;
+        }
+        self.pull();
+        true
     }
 
     pub fn push_back(&mut self, val: T) {
-        self.list.push_back(&val);
+        match self.ops.as_mut() {
+            Some(ops) => ops.push(ListOp::PushBack(self.list.encode_value(&val))),
+            None => {
+                self.list.push_back(&val);
+                self.version += 1;
+            }
+        }
         self.cache.push_back(val);
     }
 
     pub fn push_front(&mut self, val: T) {
-        self.list.push_front(&val);
+        match self.ops.as_mut() {
+            Some(ops) => ops.push(ListOp::PushFront(self.list.encode_value(&val))),
+            None => {
+                self.list.push_front(&val);
+                self.version += 1;
+            }
+        }
         self.cache.push_front(val);
     }
 
+    /// Adds all values to the back of the list and the cache like [List::push_back_all].
+    pub fn push_back_all(&mut self, vals: Vec<T>) {
+        match self.ops.as_mut() {
+            Some(ops) => ops.extend(
+                vals.iter()
+                    .map(|v| ListOp::PushBack(self.list.encode_value(v))),
+            ),
+            None if !vals.is_empty() => {
+                self.list.push_back_all(&vals);
+                self.version += 1;
+            }
+            None => {}
+        }
+        self.cache.extend(vals);
+    }
+
+    /// Adds all values to the front of the list and the cache like [List::push_front_all].
+    pub fn push_front_all(&mut self, vals: Vec<T>) {
+        match self.ops.as_mut() {
+            Some(ops) => ops.extend(
+                vals.iter()
+                    .map(|v| ListOp::PushFront(self.list.encode_value(v))),
+            ),
+            None if !vals.is_empty() => {
+                self.list.push_front_all(&vals);
+                self.version += 1;
+            }
+            None => {}
+        }
+        for val in vals {
+            self.cache.push_front(val);
+        }
+    }
+
     pub fn pop_back(&mut self) -> Option<T> {
-        self.list.pop_back();
+        match self.ops.as_mut() {
+            Some(ops) => ops.push(ListOp::PopBack),
+            None => {
+                self.list.pop_back();
+                self.version += 1;
+            }
+        }
         self.cache.pop_back()
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
-        self.list.pop_front();
+        match self.ops.as_mut() {
+            Some(ops) => ops.push(ListOp::PopFront),
+            None => {
+                self.list.pop_front();
+                self.version += 1;
+            }
+        }
         self.cache.pop_front()
     }
 
@@ -238,7 +1075,69 @@ where
 
     pub fn insert(&mut self, index: usize, val: T) {
         self.cache.insert(index, val);
-        self.list.push_back(self.cache.get(index).unwrap());
+        let val = self.cache.get(index).unwrap();
+        match self.ops.as_mut() {
+            Some(ops) => ops.push(ListOp::PushBack(self.list.encode_value(val))),
+            None => {
+                self.list.push_back(val);
+                self.version += 1;
+            }
+        }
+    }
+
+    /// Removes the occurrences of the value from the list like [List::remove] and keeps the cache in sync.
+    /// Returns the number of removed values from the cache.
+    pub fn remove(&mut self, val: &T, count: isize) -> usize
+    where
+        T: PartialEq,
+    {
+        match self.ops.as_mut() {
+            Some(ops) => ops.push(ListOp::Remove(self.list.encode_value(val), count)),
+            None => {
+                self.list.remove(val, count);
+                self.version += 1;
+            }
+        }
+
+        let max = if count == 0 {
+            usize::MAX
+        } else {
+            count.unsigned_abs()
+        };
+        let mut removed = 0;
+        if count >= 0 {
+            let mut i = 0;
+            while i < self.cache.len() && removed < max {
+                if self.cache[i] == *val {
+                    self.cache.remove(i);
+                    removed += 1;
+                } else {
+                    i += 1;
+                }
+            }
+        } else {
+            let mut i = self.cache.len();
+            while i > 0 && removed < max {
+                i -= 1;
+                if self.cache[i] == *val {
+                    self.cache.remove(i);
+                    removed += 1;
+                }
+            }
+        }
+        removed
+    }
+
+    /// Removes all values from the list like [List::drain] and clears the cache.
+    /// Deferred manipulations are flushed before.
+    ///
+    /// The values are returned as they were stored in Redis, so values pushed by other instances are included.
+    pub fn drain(&mut self) -> Vec<T> {
+        self.flush();
+        let vals = self.list.drain();
+        self.cache.clear();
+        self.version = 0;
+        vals
     }
 
     pub fn front(&self) -> Option<&T> {
@@ -254,6 +1153,92 @@ where
     }
 }
 
+impl<T> ListCache<T> {
+    /// Sends all deferred manipulations in one pipeline to the redis server.
+    /// Does nothing, if the sync is not deferred by [ListCache::defer_sync].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the manipulations cannot be sent. Use [ListCache::try_flush] to handle the error.
+    pub fn flush(&mut self) {
+        self.try_flush().expect("Failed to flush list");
+    }
+
+    /// Sends all deferred manipulations like [ListCache::flush], but returns the error instead of panicking.
+    /// The manipulations are kept on an error, so they are sent again by the next flush.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::{List, ListCache};
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut list = ListCache::without_load("test_list_try_flush", client.clone());
+    /// list.clear();
+    /// list.defer_sync();
+    /// list.push_back(1);
+    /// list.try_flush().unwrap();
+    /// assert_eq!(List::<i32>::new("test_list_try_flush", client).len(), 1);
+    /// list.clear();
+    /// ```
+    pub fn try_flush(&mut self) -> RedisResult<()> {
+        let ops = match self.ops.as_ref() {
+            Some(ops) if !ops.is_empty() => ops,
+            _ => return Ok(()),
+        };
+
+        let count = ops.len() as u64;
+        let mut pipe = redis::pipe();
+        for op in ops {
+            match op {
+                ListOp::PushBack(v) => pipe.rpush(&self.list.key, v),
+                ListOp::PushFront(v) => pipe.lpush(&self.list.key, v),
+                ListOp::PopBack => pipe.rpop(&self.list.key, None),
+                ListOp::PopFront => pipe.lpop(&self.list.key, None),
+                ListOp::Remove(v, count) => pipe.lrem(&self.list.key, *count, v),
+            }
+            .ignore();
+        }
+        pipe.incr(self.list.version_key(), count).ignore();
+
+        let mut conn = self.list.client.get_connection()?;
+        instrumented("flush", &self.list.key, || pipe.query::<()>(&mut conn))?;
+        if let Some(ops) = self.ops.as_mut() {
+            ops.clear();
+        }
+        self.version += count;
+        Ok(())
+    }
+}
+
+impl<T> Clone for ListCache<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new handle to the same list in Redis with its own cache, which is pulled from Redis.
+    /// Deferred manipulations of this instance are not part of the clone, until they are flushed.
+    fn clone(&self) -> Self {
+        let mut s = Self {
+            list: self.list.clone(),
+            cache: VecDeque::new(),
+            ops: None,
+            version: 0,
+        };
+        s.pull();
+        s
+    }
+}
+
+impl<T> Drop for ListCache<T> {
+    /// Flushes the deferred manipulations.
+    /// An error is ignored, because a panic in drop aborts the process while unwinding.
+    /// While unwinding, nothing is sent at all.
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            let _ = self.try_flush();
+        }
+    }
+}
+
 impl<T> Deref for ListCache<T> {
     type Target = List<T>;
 
@@ -267,3 +1252,16 @@ impl<T> DerefMut for ListCache<T> {
         &mut self.list
     }
 }
+
+impl<T> Index<usize> for ListCache<T> {
+    type Output = T;
+
+    /// Returns the cached value at the index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds, like [Vec].
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.cache[index]
+    }
+}
@@ -0,0 +1,55 @@
+//! # Metrics
+//! This module contains the hook to record metrics of all Redis operations.
+use std::sync::OnceLock;
+use std::time::Duration;
+use thiserror::Error;
+
+static METRICS: OnceLock<Box<dyn Metrics>> = OnceLock::new();
+
+#[derive(Error, Debug)]
+pub enum MetricsError {
+    #[error("Metrics are already registered")]
+    AlreadyRegistered,
+}
+
+/// A callback, which is called after every Redis operation.
+///
+/// It can be used to export operation counters and latencies to your metrics system,
+/// without depending on any specific crate.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::{set_metrics, Metrics};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::time::Duration;
+///
+/// struct Counter(AtomicUsize);
+///
+/// impl Metrics for Counter {
+///     fn record(&self, op: &str, key: &str, latency: Duration, ok: bool) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// set_metrics(Counter(AtomicUsize::new(0))).unwrap();
+/// ```
+pub trait Metrics: Send + Sync {
+    /// Records a finished operation with the name of the operation, the key, how long it took
+    /// and whether it was successful.
+    fn record(&self, op: &str, key: &str, latency: Duration, ok: bool);
+}
+
+/// Registers the metrics callback for all types.
+/// It can only be registered once, so it should be done at the start of your service.
+///
+/// If no metrics are registered, nothing is measured.
+pub fn set_metrics(metrics: impl Metrics + 'static) -> Result<(), MetricsError> {
+    METRICS
+        .set(Box::new(metrics))
+        .map_err(|_| MetricsError::AlreadyRegistered)
+}
+
+/// Returns the registered metrics callback, if there is one.
+pub(crate) fn metrics() -> Option<&'static dyn Metrics> {
+    METRICS.get().map(|m| m.as_ref())
+}
@@ -3,14 +3,31 @@
 //! This crate provides a set of types that can be stored in Redis. The types are:
 //!
 //! * [bool](redis::Dbool)
+//! * [Flag](redis::DFlag)
 //! * Integer types:
 //!     * signed Integer: [i8](redis::Di8), [i16](redis::Di16), [i32](redis::Di32), [i64](redis::Di64), [isize](redis::Disize)
 //!     * unsigned Integer: [u8](redis::Du8), [u16](redis::Du16), [u32](redis::Du32), [u64](redis::Du64), [usize](redis::Dusize)
 //! * [String](redis::DString)
+//! * [Result](redis::DResult)
 //! * [List](redis::List)
+//! * [DVec](redis::DVec)
+//! * [BitMap](redis::BitMap)
+//! * [HyperLogLog](redis::HyperLogLog)
+//! * [DelayQueue](redis::DelayQueue)
+//! * [BoundedQueue](redis::BoundedQueue)
+//! * [TtlQueue](redis::TtlQueue)
+//! * [Channel](redis::Channel)
+//! * [RpcClient](redis::RpcClient) and [RpcServer](redis::RpcServer)
+//! * [PeriodicLoad](redis::PeriodicLoad)
+//! * [Timer](redis::Timer)
+//! * [Namespace](redis::Namespace) to create the types with a key prefix
 //! * Sync types:
 //!     * [Mutex](redis::Mutex)
 //!     * [ClockOrdered](redis::ClockOrdered)
+//!     * [LeaderElection](redis::LeaderElection)
+//!     * [CountdownLatch](redis::CountdownLatch)
+//!     * [Scheduler](redis::Scheduler)
+//!     * [OnceValue](redis::OnceValue)
 //!
 //! This crate implements the most common traits for the primitive types, so it is frictionless to use them in place.
 //! The methods of the types can be seen in the documentation of [Generic](redis::Generic).
@@ -41,26 +58,82 @@
 //!
 //! It is possible to implement your own complex types by implementing the [BackedType](crate::BackedType) trait.
 //! But it should not be needed as long as your type implements some or all of the various [Ops](https://doc.rust-lang.org/std/ops/index.html) traits.
+mod backoff;
+mod batch;
+mod bitmap;
 mod bool_type;
+mod bounded_queue;
+mod channel;
 mod clock;
+mod compression;
+mod connector;
+mod countdown_latch;
+mod delay_queue;
+mod dvec;
+mod error;
+mod flag;
 mod generic;
 mod helper;
+mod hyperloglog;
 mod integer;
+mod leader_election;
 mod list;
+mod metrics;
 mod mutex;
+mod namespace;
+mod once_value;
+mod periodic_load;
+mod readonly;
+mod result_type;
+mod rpc;
 mod rwlock;
+mod scheduler;
+mod snapshot;
 mod string;
+mod timer;
+mod ttl_queue;
 
-pub(crate) use helper::apply_operator;
+pub(crate) use helper::{apply_checked_operator, apply_operator, instrumented, DEFAULT_SEPARATOR};
+#[cfg(feature = "async")]
+pub(crate) use helper::{instrumented_async, release_in_background};
 
+pub use backoff::Backoff;
+pub use batch::Batch;
+pub use bitmap::BitMap;
 pub use bool_type::TBool as Dbool;
-pub use clock::ClockOrdered;
-pub use generic::Generic;
+pub use bounded_queue::BoundedQueue;
+pub use channel::Channel;
+pub use clock::{ClockOrdered, ClockOrderedError};
+pub use compression::Compression;
+pub use connector::{ConnectionGuard, Connector, ReplicaConfig};
+pub use countdown_latch::CountdownLatch;
+pub use delay_queue::DelayQueue;
+pub use dvec::DVec;
+pub use error::Error;
+pub use flag::DFlag;
+pub use generic::{Generic, GenericError};
+pub use hyperloglog::HyperLogLog;
 pub use integer::{
     Ti16 as Di16, Ti32 as Di32, Ti64 as Di64, Ti8 as Di8, Tisize as Disize, Tu16 as Du16,
     Tu32 as Du32, Tu64 as Du64, Tu8 as Du8, Tusize as Dusize,
 };
-pub use list::{List, ListCache, ListIter};
+pub use leader_election::LeaderElection;
+pub use list::{List, ListCache, ListIter, ListPages};
+pub use metrics::{set_metrics, Metrics, MetricsError};
+#[cfg(feature = "async")]
+pub use mutex::AsyncGuard;
 pub use mutex::{Guard, LockError, Mutex};
-pub use rwlock::RwLock;
+pub use namespace::Namespace;
+pub use once_value::OnceValue;
+pub use periodic_load::PeriodicLoad;
+pub use readonly::ReadOnly;
+pub use result_type::TResult as DResult;
+pub use rpc::{RpcClient, RpcError, RpcServer};
+#[cfg(feature = "async")]
+pub use rwlock::{AsyncRwLockReadGuard, AsyncRwLockWriteGuard};
+pub use rwlock::{RwLock, RwLockError};
+pub use scheduler::Scheduler;
+pub use snapshot::snapshot;
 pub use string::TString as DString;
+pub use timer::Timer;
+pub use ttl_queue::TtlQueue;
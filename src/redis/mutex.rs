@@ -1,7 +1,11 @@
-use crate::redis::Generic;
+use crate::redis::{instrumented, Backoff, ConnectionGuard, Generic, DEFAULT_SEPARATOR};
+#[cfg(feature = "async")]
+use crate::redis::{instrumented_async, release_in_background};
+use redis::RedisResult;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::ops::{Deref, DerefMut};
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,6 +18,10 @@ pub enum LockError {
     NoConnection,
     #[error("Lock expired with id #{0}")]
     LockExpired(usize),
+    #[error("Mutex is not reentrant")]
+    NotReentrant,
+    #[error("Mutex is poisoned, because a guard was dropped during a panic")]
+    Poisoned,
     #[error("Error by Redis")]
     Redis(#[from] redis::RedisError),
 }
@@ -36,14 +44,50 @@ impl From<i8> for LockNum {
 
 /// The lock script.
 /// It is used to lock a value in Redis, so that only one instance can access it at a time.
-/// Takes 3 Arguments:
+/// Takes 4 Arguments:
 /// 1. The key of the value to lock,
 /// 2. The timeout in seconds,
-/// 3. The value to store.
+/// 3. The value to store,
+/// 4. The separator of the derived keys.
 const LOCK_SCRIPT: &str = r#"
-local val = redis.call("get", ARGV[1] .. ":lock")
+local val = redis.call("get", ARGV[1] .. ARGV[4] .. "lock")
 if val == false or val == ARGV[3] then
-    redis.call("setex", ARGV[1] .. ":lock", ARGV[2], ARGV[3])
+    redis.call("setex", ARGV[1] .. ARGV[4] .. "lock", ARGV[2], ARGV[3])
+    return 1
+end
+return 0"#;
+
+/// The fair lock script.
+/// It is used to lock a value in Redis in the order of the lock requests.
+/// Every waiter is enqueued once and only the head of the queue gets the lock.
+/// Waiters refresh a heartbeat on every attempt, so dead waiters are removed from the head.
+///
+/// Takes 4 Arguments:
+/// 1. The key of the value to lock,
+/// 2. The timeout in seconds,
+/// 3. The value to store,
+/// 4. The separator of the derived keys.
+const FAIR_LOCK_SCRIPT: &str = r#"
+local lock_key = ARGV[1] .. ARGV[4] .. "lock"
+local queue_key = ARGV[1] .. ARGV[4] .. "queue"
+local val = redis.call("get", lock_key)
+if val == ARGV[3] then
+    redis.call("setex", lock_key, ARGV[2], ARGV[3])
+    return 1
+end
+redis.call("set", ARGV[1] .. ARGV[4] .. "waiting" .. ARGV[4] .. ARGV[3], 1, "px", 1000)
+if redis.call("lpos", queue_key, ARGV[3]) == false then
+    redis.call("rpush", queue_key, ARGV[3])
+end
+local head = redis.call("lindex", queue_key, 0)
+while head and redis.call("exists", ARGV[1] .. ARGV[4] .. "waiting" .. ARGV[4] .. head) == 0 do
+    redis.call("lpop", queue_key)
+    head = redis.call("lindex", queue_key, 0)
+end
+if val == false and head == ARGV[3] then
+    redis.call("lpop", queue_key)
+    redis.call("del", ARGV[1] .. ARGV[4] .. "waiting" .. ARGV[4] .. ARGV[3])
+    redis.call("setex", lock_key, ARGV[2], ARGV[3])
     return 1
 end
 return 0"#;
@@ -51,13 +95,14 @@ return 0"#;
 /// The drop script.
 /// It is used to drop a value in Redis, so that only the instance that locked it can drop it.
 ///
-/// Takes 2 Arguments:
+/// Takes 3 Arguments:
 /// 1. The key of the value to drop,
-/// 2. The value to check.
+/// 2. The value to check,
+/// 3. The separator of the derived keys.
 const DROP_SCRIPT: &str = r#"
-local current_lock = redis.call("get", ARGV[1] .. ":lock")
+local current_lock = redis.call("get", ARGV[1] .. ARGV[3] .. "lock")
 if current_lock == ARGV[2] then
-    redis.call("del", ARGV[1] .. ":lock")
+    redis.call("del", ARGV[1] .. ARGV[3] .. "lock")
     return 1
 end
 return 0"#;
@@ -66,22 +111,24 @@ return 0"#;
 /// It is used to generate a uuid for the lock.
 /// It is a very simple counter that is stored in Redis and returns all numbers only once.
 ///
-/// Takes 1 Argument:
-/// 1. The key of the value to lock.
+/// Takes 2 Arguments:
+/// 1. The key of the value to lock,
+/// 2. The separator of the derived keys.
 const UUID_SCRIPT: &str = r#"
-redis.call("incr", ARGV[1] .. ":uuids")
-local val = redis.call("get", ARGV[1] .. ":uuids")
+redis.call("incr", ARGV[1] .. ARGV[2] .. "uuids")
+local val = redis.call("get", ARGV[1] .. ARGV[2] .. "uuids")
 return val"#;
 
 /// The store script.
 /// It is used to store a value in Redis with a lock.
 ///
-/// Takes 3 Arguments:
+/// Takes 4 Arguments:
 /// 1. The key of the value to store,
 /// 2. The uuid of the lock object,
-/// 3. The value to store.
+/// 3. The value to store,
+/// 4. The separator of the derived keys.
 const STORE_SCRIPT: &str = r#"
-local current_lock = redis.call("get", ARGV[1] .. ":lock")
+local current_lock = redis.call("get", ARGV[1] .. ARGV[4] .. "lock")
 if current_lock == ARGV[2] then
     redis.call("set", ARGV[1], ARGV[3])
     return 1
@@ -91,17 +138,33 @@ return 0"#;
 /// The load script.
 /// It is used to load a value from Redis with a lock.
 ///
-/// Takes 2 Arguments:
+/// Takes 3 Arguments:
 /// 1. The key of the value to load,
-/// 2. The uuid of the lock.
+/// 2. The uuid of the lock,
+/// 3. The separator of the derived keys.
 const LOAD_SCRIPT: &str = r#"
-local current_lock = redis.call("get", ARGV[1] .. ":lock")
+local current_lock = redis.call("get", ARGV[1] .. ARGV[3] .. "lock")
 if current_lock == ARGV[2] then
     local val = redis.call("get", ARGV[1])
     return val
 end
 return nil"#;
 
+/// Returns the key of the poison marker of the value.
+fn poison_key(key: &str, separator: &str) -> String {
+    format!("{}{}poisoned", key, separator)
+}
+
+/// Generates a new uuid for the lock with the uuid script.
+fn generate_uuid(conn: &mut ConnectionGuard, key: &str, separator: &str) -> RedisResult<usize> {
+    instrumented("uuid", key, || {
+        redis::Script::new(UUID_SCRIPT)
+            .arg(key)
+            .arg(separator)
+            .invoke::<usize>(conn)
+    })
+}
+
 /// The RedisMutex struct.
 ///
 /// It is used to lock a value in Redis, so that only one instance can access it at a time.
@@ -110,10 +173,30 @@ return nil"#;
 ///
 /// The lock is released when the guard is dropped or it expires.
 /// The default expiration time is 1000ms. If you need more time, use the [Guard::expand()] function.
+///
+/// If you need to lock the value again while holding the guard, use [Mutex::new_reentrant].
+/// If you need the lock to be granted in the order of the requests, use [Mutex::new_fair].
+///
+/// While the value is locked by another instance, the attempts are delayed with a [Backoff],
+/// which can be configured with [Mutex::with_backoff].
+///
+/// Like the Mutex in std, the Mutex is poisoned, if a guard is dropped while its thread panics,
+/// because the value can be inconsistent. Afterward [Mutex::lock] returns [LockError::Poisoned] on all instances,
+/// until the poison is cleared with [Mutex::clear_poison].
+///
+/// The lock is stored in derived keys like `<key>:lock`.
+/// If `:` clashes with the naming convention of your keys, use [Mutex::with_separator].
 pub struct Mutex<T> {
-    conn: Option<redis::Connection>,
+    conn: Option<ConnectionGuard>,
     data: Generic<T>,
+    /// The uuid is generated on the first lock, so the builders can change the separator before.
+    /// It is 0 until then, because the uuid script starts at 1.
     uuid: usize,
+    reentrant: bool,
+    fair: bool,
+    depth: usize,
+    backoff: Backoff,
+    separator: String,
 }
 
 impl<T> Mutex<T>
@@ -121,23 +204,100 @@ where
     T: Serialize + DeserializeOwned,
 {
     pub fn new(data: Generic<T>) -> Self {
-        let mut conn = data
-            .client
-            .get_connection()
-            .expect("Failed to get connection to Redis");
-
-        let uuid = redis::Script::new(UUID_SCRIPT)
-            .arg(&data.key)
-            .invoke::<usize>(&mut conn)
-            .expect("Failed to get uuid");
-
         Self {
             data,
-            conn: Some(conn),
-            uuid,
+            conn: None,
+            uuid: 0,
+            reentrant: false,
+            fair: false,
+            depth: 0,
+            backoff: Backoff::default(),
+            separator: DEFAULT_SEPARATOR.to_string(),
         }
     }
 
+    /// Sets the separator between the key and the suffixes of the derived keys, e.g. `<key>/lock` for `/`.
+    /// The default is `:`.
+    ///
+    /// All instances, which lock the same value, have to use the same separator.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use dtypes::redis::Mutex;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32 = i32::new("test_separator_example", client);
+    /// let mut lock = Mutex::new(i32).with_separator("/");
+    /// let mut guard = lock.lock().unwrap();
+    /// guard.store(1).unwrap();
+    /// assert_eq!(*guard, 1);
+    /// ```
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Sets the backoff between two attempts to lock the value.
+    ///
+    /// For a Mutex created with [Mutex::new_fair], the maximum backoff must be lower than 1000ms,
+    /// otherwise the waiter loses its place in the queue.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Creates a new reentrant Mutex.
+    /// The value can be locked again with [Guard::lock] while holding the guard.
+    /// The lock is only released in Redis, when the outermost guard is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use dtypes::redis::Mutex;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32 = i32::new("test_reentrant_example", client);
+    /// let mut lock = Mutex::new_reentrant(i32);
+    /// let mut guard = lock.lock().unwrap();
+    /// {
+    ///     let mut inner = guard.lock().unwrap();
+    ///     inner.store(1).unwrap();
+    /// } // the lock is still held here
+    /// guard.store(2).unwrap();
+    /// assert_eq!(*guard, 2);
+    /// ```
+    pub fn new_reentrant(data: Generic<T>) -> Self {
+        let mut s = Self::new(data);
+        s.reentrant = true;
+        s
+    }
+
+    /// Creates a new fair Mutex.
+    /// The waiters are enqueued in a list in Redis and the lock is granted in FIFO order,
+    /// so no waiter can starve under contention like with the spinning [Mutex::new].
+    ///
+    /// A waiter, which stops trying for more than 1000ms, e.g. because it crashed,
+    /// loses its place in the queue.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use dtypes::redis::Mutex;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32 = i32::new("test_fair_example", client);
+    /// let mut lock = Mutex::new_fair(i32);
+    /// let mut guard = lock.lock().unwrap();
+    /// guard.store(1).unwrap();
+    /// assert_eq!(*guard, 1);
+    /// ```
+    pub fn new_fair(data: Generic<T>) -> Self {
+        let mut s = Self::new(data);
+        s.fair = true;
+        s
+    }
+
     /// Locks the value in Redis.
     /// This function blocks until the lock is acquired.
     /// It returns a guard that can be used to access the value.
@@ -202,34 +362,215 @@ where
     /// let res = guard.store(3);
     /// assert!(res.is_err(), "{:?}", res);
     /// ```
-    pub fn lock(&mut self) -> Result<Guard<T>, LockError> {
+    pub fn lock(&mut self) -> Result<Guard<'_, T>, LockError> {
+        self.acquire_lock()?;
+        let mut guard = Guard::new(self)?;
+        if guard.poisoned()? {
+            // the guard releases the lock again
+            return Err(LockError::Poisoned);
+        }
+        Ok(guard)
+    }
+
+    /// Returns true, if the Mutex is poisoned, because a guard was dropped during a panic.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use dtypes::redis::{LockError, Mutex};
+    /// use std::panic::{catch_unwind, AssertUnwindSafe};
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut lock = Mutex::new(i32::with_value(1, "test_poison_example", client));
+    /// lock.clear_poison();
+    /// let _ = catch_unwind(AssertUnwindSafe(|| {
+    ///     let _guard = lock.lock().unwrap();
+    ///     panic!("The value may be inconsistent now");
+    /// }));
+    /// assert!(lock.is_poisoned());
+    /// assert!(matches!(lock.lock(), Err(LockError::Poisoned)));
+    /// lock.clear_poison();
+    /// assert!(lock.lock().is_ok());
+    /// ```
+    pub fn is_poisoned(&self) -> bool {
+        let mut conn = self.data.get_conn();
+        instrumented("is_poisoned", &self.data.key, || {
+            redis::Cmd::exists(poison_key(&self.data.key, &self.separator)).query(&mut conn)
+        })
+        .expect("Failed to check poison")
+    }
+
+    /// Clears the poison, so the value can be locked again.
+    /// Check the value for consistency before.
+    pub fn clear_poison(&mut self) {
+        let mut conn = self.data.get_conn();
+        instrumented("clear_poison", &self.data.key, || {
+            redis::Cmd::del(poison_key(&self.data.key, &self.separator)).query::<()>(&mut conn)
+        })
+        .expect("Failed to clear poison");
+    }
+
+    /// Locks the value in Redis like [Mutex::lock], but waits asynchronously between the attempts.
+    /// Only available with the feature `async`.
+    ///
+    /// There is no async drop, so the returned guard has to be released with [AsyncGuard::release].
+    /// If it is dropped without release, the lock is released in a background task of the current tokio runtime.
+    /// Outside of a runtime, the lock lingers until it expires after 1000ms.
+    ///
+    /// The async guard cannot be locked again like [Guard::lock].
+    /// A poisoned Mutex returns [LockError::Poisoned] like [Mutex::lock].
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use dtypes::redis::Mutex;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut lock = Mutex::new(i32::new("test_lock_async_example", client));
+    /// let mut guard = lock.lock_async().await.unwrap();
+    /// guard.store(1).await.unwrap();
+    /// assert_eq!(guard.acquire().await.unwrap(), &1);
+    /// guard.release().await.unwrap();
+    /// assert!(!lock.is_locked());
+    /// # });
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn lock_async(&mut self) -> Result<AsyncGuard<'_, T>, LockError> {
+        let mut conn = self
+            .data
+            .client
+            .get_async_connection()
+            .await
+            .map_err(|_| LockError::NoConnection)?;
+        if self.uuid == 0 {
+            self.uuid = instrumented_async(
+                "uuid",
+                &self.data.key,
+                redis::Script::new(UUID_SCRIPT)
+                    .arg(&self.data.key)
+                    .arg(&self.separator)
+                    .invoke_async(&mut conn),
+            )
+            .await?;
+        }
+
+        let lock_cmd = redis::Script::new(if self.fair {
+            FAIR_LOCK_SCRIPT
+        } else {
+            LOCK_SCRIPT
+        });
+
+        let mut backoff = self.backoff.start();
+        loop {
+            let res: i8 = instrumented_async(
+                "lock",
+                &self.data.key,
+                lock_cmd
+                    .arg(&self.data.key)
+                    .arg(1)
+                    .arg(self.uuid.to_string())
+                    .arg(&self.separator)
+                    .invoke_async(&mut conn),
+            )
+            .await?;
+            if LockNum::from(res) == LockNum::Success {
+                break;
+            }
+            tokio::time::sleep(backoff.next_delay()).await;
+        }
+
+        let poisoned: bool = instrumented_async(
+            "is_poisoned",
+            &self.data.key,
+            redis::Cmd::exists(poison_key(&self.data.key, &self.separator)).query_async(&mut conn),
+        )
+        .await?;
+        let guard = AsyncGuard {
+            lock: self,
+            conn: Some(conn),
+        };
+        if poisoned {
+            guard.release().await?;
+            return Err(LockError::Poisoned);
+        }
+        Ok(guard)
+    }
+
+    /// Returns true, if the value is currently locked in Redis by any instance.
+    ///
+    /// It does not acquire the lock, so it is safe to call it from monitoring code to spot stuck locks.
+    /// Beware that the result can be outdated immediately.
+    pub fn is_locked(&self) -> bool {
+        self.current_owner().is_some()
+    }
+
+    /// Returns the uuid of the instance, which currently holds the lock in Redis.
+    /// Returns None, if the value is not locked.
+    ///
+    /// It does not acquire the lock, so it is safe to call it from monitoring code to spot stuck locks.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use dtypes::redis::Mutex;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let i32 = i32::new("test_owner_example", client);
+    /// let mut lock = Mutex::new(i32);
+    /// assert_eq!(lock.current_owner(), None);
+    /// let guard = lock.lock().unwrap();
+    /// drop(guard);
+    /// assert!(!lock.is_locked());
+    /// ```
+    pub fn current_owner(&self) -> Option<usize> {
+        let mut conn = self.data.get_conn();
+        let lock_key = format!("{}{}lock", self.data.key, self.separator);
+        instrumented("current_owner", &self.data.key, || {
+            redis::Cmd::get(&lock_key).query::<Option<usize>>(&mut conn)
+        })
+        .expect("Failed to get the owner of the lock")
+    }
+
+    /// Blocks until the lock is acquired in Redis and increases the recursion depth.
+    fn acquire_lock(&mut self) -> Result<(), LockError> {
         let mut conn = match self.conn.take() {
             Some(conn) => conn,
             None => self
                 .client
                 .get_connection()
-                .map_err(|_| LockError::LockFailed)?,
+                .map_err(|_| LockError::NoConnection)?,
         };
+        if self.uuid == 0 {
+            self.uuid = generate_uuid(&mut conn, &self.data.key, &self.separator)?;
+        }
 
-        let lock_cmd = redis::Script::new(LOCK_SCRIPT);
+        let lock_cmd = redis::Script::new(if self.fair {
+            FAIR_LOCK_SCRIPT
+        } else {
+            LOCK_SCRIPT
+        });
 
-        while LockNum::from(
-            lock_cmd
-                .arg(&self.data.key)
-                .arg(1)
-                .arg(&self.uuid.to_string())
-                .invoke::<i8>(&mut conn)
-                .expect("Failed to lock. You should not see this!"),
-        ) == LockNum::Fail
-        {
-            std::hint::spin_loop();
+        let mut backoff = self.backoff.start();
+        loop {
+            let res = instrumented("lock", &self.data.key, || {
+                lock_cmd
+                    .arg(&self.data.key)
+                    .arg(1)
+                    .arg(self.uuid.to_string())
+                    .arg(&self.separator)
+                    .invoke::<i8>(&mut conn)
+            })?;
+            if LockNum::from(res) == LockNum::Success {
+                break;
+            }
+            backoff.wait();
         }
 
         // store the connection for later use
         self.conn = Some(conn);
-        let lock = Guard::new(self)?;
-
-        Ok(lock)
+        self.depth += 1;
+        Ok(())
     }
 }
 
@@ -265,6 +606,36 @@ where
         })
     }
 
+    /// Locks the value again, while this guard is held.
+    /// This is only allowed for a Mutex created with [Mutex::new_reentrant],
+    /// otherwise it returns [LockError::NotReentrant].
+    ///
+    /// The lock in Redis is released, when the outermost guard is dropped.
+    pub fn lock(&mut self) -> Result<Guard<'_, T>, LockError> {
+        if !self.lock.reentrant {
+            return Err(LockError::NotReentrant);
+        }
+
+        self.lock.acquire_lock()?;
+        Guard::new(self.lock)
+    }
+
+    /// Returns true, if the Mutex is poisoned. It uses the connection of the lock.
+    fn poisoned(&mut self) -> Result<bool, LockError> {
+        let conn = self.lock.conn.as_mut().ok_or(LockError::NoConnection)?;
+        let poisoned = instrumented("is_poisoned", &self.lock.data.key, || {
+            redis::Cmd::exists(poison_key(&self.lock.data.key, &self.lock.separator)).query(conn)
+        })?;
+        Ok(poisoned)
+    }
+
+    /// Returns the uuid of the Mutex, which holds this guard.
+    /// It is the same as returned by [Mutex::current_owner] while the lock is held,
+    /// so it can be used in logs to correlate, which instance held the lock.
+    pub fn owner_id(&self) -> usize {
+        self.lock.uuid
+    }
+
     /// Expands the lock time by 2000ms from the point on its called.
     /// This is useful if you need to access the value for a longer time.
     ///
@@ -276,11 +647,47 @@ where
         }
 
         let conn = self.lock.conn.as_mut().expect("Connection should be there");
-        let expand = redis::Cmd::expire(format!("{}:lock", &self.lock.data.key), 2);
-        expand.execute(conn);
+        let expand = redis::Cmd::expire(
+            format!("{}{}lock", &self.lock.data.key, &self.lock.separator),
+            2,
+        );
+        instrumented("expand", &self.lock.data.key, || expand.query::<()>(conn))
+            .expect("Failed to expand lock");
         self.expanded = true;
     }
 
+    /// Returns the remaining time of the lock in Redis with PTTL.
+    /// Returns None, if the lock is already expired.
+    ///
+    /// So the holder can decide, if it needs to [Guard::expand] the lock before doing more work.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::Di32 as i32;
+    /// use dtypes::redis::Mutex;
+    /// use std::time::Duration;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut lock = Mutex::new(i32::new("test_remaining_lease_example", client));
+    /// let mut guard = lock.lock().unwrap();
+    /// if guard.remaining_lease().unwrap_or_default() < Duration::from_millis(500) {
+    ///     guard.expand();
+    /// }
+    /// ```
+    pub fn remaining_lease(&self) -> Option<Duration> {
+        let mut conn = self.lock.data.get_conn();
+        let pttl = redis::Cmd::pttl(format!(
+            "{}{}lock",
+            &self.lock.data.key, &self.lock.separator
+        ));
+        let millis: i64 = instrumented("remaining_lease", &self.lock.data.key, || {
+            pttl.query(&mut conn)
+        })
+        .expect("Failed to get remaining lease");
+        // PTTL returns a negative value, if there is no lock or it has no expiry
+        u64::try_from(millis).ok().map(Duration::from_millis)
+    }
+
     /// Stores the value in Redis.
     /// This function blocks until the value is stored.
     /// Disables the store operation of the guarded value.
@@ -290,12 +697,14 @@ where
     {
         let conn = self.lock.conn.as_mut().ok_or(LockError::NoConnection)?;
         let script = redis::Script::new(STORE_SCRIPT);
-        let result: i8 = script
-            .arg(&self.lock.data.key)
-            .arg(self.lock.uuid)
-            .arg(serde_json::to_string(&value).expect("Failed to serialize value"))
-            .invoke(conn)
-            .expect("Failed to store value. You should not see this!");
+        let result: i8 = instrumented("store", &self.lock.data.key, || {
+            script
+                .arg(&self.lock.data.key)
+                .arg(self.lock.uuid)
+                .arg(self.lock.data.encode_value(&value))
+                .arg(&self.lock.separator)
+                .invoke(conn)
+        })?;
         if result == 0 {
             return Err(LockError::LockExpired(self.lock.uuid));
         }
@@ -306,30 +715,24 @@ where
     /// Loads the value from Redis.
     /// This function blocks until the value is loaded.
     /// Shadows the load operation of the guarded value.
-    pub fn acquire(&mut self) -> &T {
-        self.lock.data.cache = self.try_get();
-        self.lock.data.cache.as_ref().unwrap()
-    }
-
-    fn try_get(&mut self) -> Option<T> {
-        let conn = self
-            .lock
-            .conn
-            .as_mut()
-            .ok_or(LockError::NoConnection)
-            .expect("Connection should be there");
+    ///
+    /// Returns an [Error](crate::redis::Error), if the value cannot be loaded from Redis.
+    pub fn acquire(&mut self) -> Result<&T, crate::redis::Error> {
+        self.lock.data.cache = self.try_get()?;
+        Ok(self.lock.data.cache.as_ref().unwrap())
+    }
+
+    fn try_get(&mut self) -> Result<Option<T>, crate::redis::Error> {
+        let conn = self.lock.conn.as_mut().ok_or(LockError::NoConnection)?;
         let script = redis::Script::new(LOAD_SCRIPT);
-        let result: Option<String> = script
-            .arg(&self.lock.data.key)
-            .arg(self.lock.uuid)
-            .invoke(conn)
-            .expect("Failed to load value. You should not see this!");
-        let result = result?;
-
-        if result == "nil" {
-            return None;
-        }
-        Some(serde_json::from_str(&result).expect("Failed to deserialize value"))
+        let result: Option<Vec<u8>> = instrumented("acquire", &self.lock.data.key, || {
+            script
+                .arg(&self.lock.data.key)
+                .arg(self.lock.uuid)
+                .arg(&self.lock.separator)
+                .invoke(conn)
+        })?;
+        Ok(result.map(|v| self.lock.data.decode_value(&v)))
     }
 }
 
@@ -355,21 +758,139 @@ where
 
 impl<T> Drop for Guard<'_, T> {
     fn drop(&mut self) {
-        let conn = self.lock.conn.as_mut().expect("Connection should be there");
-        let script = redis::Script::new(DROP_SCRIPT);
-        script
-            .arg(&self.lock.data.key)
-            .arg(self.lock.uuid)
-            .invoke::<()>(conn)
-            .expect("Failed to drop lock. You should not see this!");
+        let panicking = std::thread::panicking();
+        if panicking {
+            if let Some(conn) = self.lock.conn.as_mut() {
+                // errors are ignored, because a second panic would abort
+                let poison =
+                    redis::Cmd::set(poison_key(&self.lock.data.key, &self.lock.separator), 1);
+                let _ = instrumented("poison", &self.lock.data.key, || poison.query::<()>(conn));
+            }
+        }
+
+        self.lock.depth -= 1;
+        if self.lock.depth > 0 {
+            return;
+        }
+
+        // errors are ignored, because drop cannot return them. The lock expires after its lease instead.
+        if let Some(conn) = self.lock.conn.as_mut() {
+            let script = redis::Script::new(DROP_SCRIPT);
+            let _ = instrumented("unlock", &self.lock.data.key, || {
+                script
+                    .arg(&self.lock.data.key)
+                    .arg(self.lock.uuid)
+                    .arg(&self.lock.separator)
+                    .invoke::<()>(conn)
+            });
+        }
+    }
+}
+
+/// The guard of [Mutex::lock_async].
+/// It has to be released with [AsyncGuard::release], because there is no async drop.
+#[cfg(feature = "async")]
+pub struct AsyncGuard<'a, T> {
+    lock: &'a mut Mutex<T>,
+    conn: Option<redis::aio::Connection>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> AsyncGuard<'a, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Stores the value in Redis like [Guard::store].
+    pub async fn store(&mut self, value: T) -> Result<(), LockError> {
+        let conn = self.conn.as_mut().ok_or(LockError::NoConnection)?;
+        let result: i8 = instrumented_async(
+            "store",
+            &self.lock.data.key,
+            redis::Script::new(STORE_SCRIPT)
+                .arg(&self.lock.data.key)
+                .arg(self.lock.uuid)
+                .arg(self.lock.data.encode_value(&value))
+                .arg(&self.lock.separator)
+                .invoke_async(conn),
+        )
+        .await?;
+        if result == 0 {
+            return Err(LockError::LockExpired(self.lock.uuid));
+        }
+        self.lock.data.cache = Some(value);
+        Ok(())
+    }
+
+    /// Loads the value from Redis like [Guard::acquire].
+    pub async fn acquire(&mut self) -> Result<&T, crate::redis::Error> {
+        let conn = self.conn.as_mut().ok_or(LockError::NoConnection)?;
+        let result: Option<Vec<u8>> = instrumented_async(
+            "acquire",
+            &self.lock.data.key,
+            redis::Script::new(LOAD_SCRIPT)
+                .arg(&self.lock.data.key)
+                .arg(self.lock.uuid)
+                .arg(&self.lock.separator)
+                .invoke_async(conn),
+        )
+        .await?;
+        self.lock.data.cache = result.map(|v| self.lock.data.decode_value(&v));
+        Ok(self.lock.data.cache.as_ref().unwrap())
+    }
+
+    /// Releases the lock in Redis.
+    /// Returns [LockError::UnlockFailed], if the lock was already expired and is held by no one or another instance.
+    pub async fn release(mut self) -> Result<(), LockError> {
+        let mut conn = self.conn.take().ok_or(LockError::NoConnection)?;
+        let released: i8 = instrumented_async(
+            "unlock",
+            &self.lock.data.key,
+            redis::Script::new(DROP_SCRIPT)
+                .arg(&self.lock.data.key)
+                .arg(self.lock.uuid)
+                .arg(&self.lock.separator)
+                .invoke_async(&mut conn),
+        )
+        .await?;
+        if released == 0 {
+            return Err(LockError::UnlockFailed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Deref for AsyncGuard<'_, T> {
+    type Target = Generic<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.lock.data
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Drop for AsyncGuard<'_, T> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            release_in_background(
+                conn,
+                DROP_SCRIPT,
+                self.lock.data.key.clone(),
+                self.lock.uuid,
+                self.lock.separator.clone(),
+            );
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Mutex;
-    use crate::redis::Di32;
+    #[cfg(feature = "messagepack")]
+    use crate::redis::Generic;
+    use crate::redis::{DString, Di32};
     use std::thread;
+    use std::time::Duration;
     #[test]
     fn test_create_lock() {
         let client = redis::Client::open("redis://localhost:6379").unwrap();
@@ -393,4 +914,149 @@ mod tests {
             t1.join().expect("Failed to join thread1");
         });
     }
+
+    #[test]
+    fn test_current_owner() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut lock: Mutex<i32> = Mutex::new(Di32::new("test_owner_locking", client.clone()));
+        let observer: Mutex<i32> = Mutex::new(Di32::new("test_owner_locking", client));
+        assert!(!observer.is_locked());
+
+        let guard = lock.lock().unwrap();
+        let uuid = guard.owner_id();
+        assert_ne!(uuid, 0);
+        assert!(observer.is_locked());
+        assert_eq!(observer.current_owner(), Some(uuid));
+        drop(guard);
+        assert_eq!(observer.current_owner(), None);
+    }
+
+    #[test]
+    fn test_separator() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut conn = client.get_connection().unwrap();
+        redis::cmd("DEL")
+            .arg("test_separator_locking:uuids")
+            .execute(&mut conn);
+        let mut lock: Mutex<i32> =
+            Mutex::new(Di32::new("test_separator_locking", client.clone())).with_separator("/");
+        let default: Mutex<i32> = Mutex::new(Di32::new("test_separator_locking", client.clone()));
+
+        let guard = lock.lock().unwrap();
+        let exists: bool = redis::Cmd::exists("test_separator_locking/lock")
+            .query(&mut conn)
+            .unwrap();
+        assert!(exists);
+        // the uuid is generated with the separator, so no key with the default separator is left behind
+        let exists: bool = redis::Cmd::exists("test_separator_locking:uuids")
+            .query(&mut conn)
+            .unwrap();
+        assert!(!exists);
+        // the locks with different separators do not see each other
+        assert!(!default.is_locked());
+        drop(guard);
+        assert!(!lock.is_locked());
+    }
+
+    #[cfg(feature = "messagepack")]
+    #[test]
+    fn test_messagepack_lock() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let data: Generic<Vec<i32>> =
+            Generic::with_messagepack("test_messagepack_locking", client.clone());
+        let mut lock = Mutex::new(data);
+        {
+            let mut guard = lock.lock().unwrap();
+            guard.store(vec![1, 2, 3]).unwrap();
+            assert_eq!(guard.acquire().unwrap(), &vec![1, 2, 3]);
+        }
+
+        let mut conn = client.get_connection().unwrap();
+        let raw: Vec<u8> = redis::Cmd::get("test_messagepack_locking")
+            .query(&mut conn)
+            .unwrap();
+        assert_eq!(raw, [0x93, 0x01, 0x02, 0x03]);
+        let mut other: Generic<Vec<i32>> =
+            Generic::with_messagepack("test_messagepack_locking", client);
+        assert_eq!(other.acquire(), &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_remaining_lease() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut lock: Mutex<i32> = Mutex::new(Di32::new("test_lease_locking", client));
+        let mut guard = lock.lock().unwrap();
+        let lease = guard.remaining_lease().unwrap();
+        assert!(lease <= Duration::from_secs(1));
+        guard.expand();
+        assert!(guard.remaining_lease().unwrap() > Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_fair_lock() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut conn = client.get_connection().unwrap();
+        redis::cmd("DEL")
+            .arg("test_fair_locking:queue")
+            .execute(&mut conn);
+        let mut holder = Mutex::new_fair(Di32::new("test_fair_locking", client.clone()));
+        let guard = holder.lock().unwrap();
+        let order = std::sync::Mutex::new(Vec::new());
+
+        thread::scope(|s| {
+            for i in 0..3 {
+                let client = client.clone();
+                let order = &order;
+                s.spawn(move || {
+                    let mut lock = Mutex::new_fair(Di32::new("test_fair_locking", client));
+                    let mut guard = lock.lock().unwrap();
+                    order.lock().unwrap().push(i);
+                    guard.store(i as i32).unwrap();
+                });
+                // the next waiter is spawned after this one is enqueued, so the enqueue order is known
+                while redis::Cmd::llen("test_fair_locking:queue")
+                    .query::<usize>(&mut conn)
+                    .unwrap()
+                    < i + 1
+                {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+            drop(guard);
+        });
+        // the waiters got the lock in the order they were enqueued
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_reentrant_lock() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let i32 = Di32::new("test_reentrant_locking", client.clone());
+        let mut lock: Mutex<i32> = Mutex::new_reentrant(i32);
+        let mut guard = lock.lock().unwrap();
+        {
+            let mut inner = guard.lock().unwrap();
+            inner.store(1).unwrap();
+            assert!(inner.lock().is_ok());
+        }
+        // the inner guards are dropped, but the lock is still held
+        guard.store(2).unwrap();
+        assert_eq!(*guard, 2);
+        drop(guard);
+
+        let i32 = Di32::new("test_reentrant_locking", client);
+        let mut lock: Mutex<i32> = Mutex::new(i32);
+        let mut guard = lock.lock().unwrap();
+        assert!(guard.lock().is_err());
+    }
+
+    #[test]
+    fn test_nil_string() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let s = DString::new("test_mutex_nil_string", client);
+        let mut lock = Mutex::new(s);
+        let mut guard = lock.lock().unwrap();
+        guard.store("nil".to_string()).unwrap();
+        assert_eq!(guard.acquire().unwrap(), "nil");
+    }
 }
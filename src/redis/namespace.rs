@@ -0,0 +1,98 @@
+//! # Namespace
+//! This module contains the namespace, which creates the types with a common key prefix.
+use crate::redis::{Connector, Generic, List, Mutex};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A client with a key prefix.
+///
+/// All types created by the namespace use `<prefix>:<name>` as key,
+/// so multiple tenants or services can share one Redis without colliding keys.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::Namespace;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let tenant = Namespace::new("test_namespace_example", client);
+/// let mut counter = tenant.generic::<i32>("counter");
+/// counter.store(1);
+/// assert_eq!(tenant.key("counter"), "test_namespace_example:counter");
+///
+/// let nested = tenant.namespace("jobs");
+/// assert_eq!(nested.key("queue"), "test_namespace_example:jobs:queue");
+/// ```
+#[derive(Clone)]
+pub struct Namespace {
+    prefix: String,
+    client: Connector,
+}
+
+impl Namespace {
+    /// Creates a new Namespace with the given prefix.
+    pub fn new(prefix: impl Into<String>, client: impl Into<Connector>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            client: client.into(),
+        }
+    }
+
+    /// Returns the key of the name in this namespace.
+    pub fn key(&self, name: &str) -> String {
+        format!("{}:{}", self.prefix, name)
+    }
+
+    /// Creates a nested namespace with `<prefix>:<name>` as prefix.
+    pub fn namespace(&self, name: &str) -> Namespace {
+        Namespace::new(self.key(name), self.client.clone())
+    }
+
+    /// Creates a [Generic] in this namespace like [Generic::new].
+    pub fn generic<T>(&self, name: &str) -> Generic<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        Generic::new(self.key(name), self.client.clone())
+    }
+
+    /// Creates a [List] in this namespace like [List::new].
+    pub fn list<T>(&self, name: &str) -> List<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        List::new(&self.key(name), self.client.clone())
+    }
+
+    /// Creates a [Mutex] in this namespace like [Mutex::new].
+    pub fn mutex<T>(&self, name: &str) -> Mutex<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        Mutex::new(self.generic(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespace() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let a = Namespace::new("test_namespace_a", client.clone());
+        let b = Namespace::new("test_namespace_b", client);
+
+        a.generic::<i32>("value").store(1);
+        b.generic::<i32>("value").store(2);
+        assert_eq!(a.generic::<i32>("value").acquire(), &1);
+        assert_eq!(b.generic::<i32>("value").acquire(), &2);
+
+        let mut list = a.list::<i32>("list");
+        list.clear();
+        list.push_back(&1);
+        assert!(b.list::<i32>("list").is_empty());
+
+        let mut lock = a.mutex::<i32>("value");
+        assert_eq!(lock.lock().unwrap().acquire().unwrap(), &1);
+    }
+}
@@ -0,0 +1,213 @@
+//! # Once Value
+//! This module contains the once value type, which initializes a value once across all instances.
+use crate::redis::compression::{decode, encode};
+use crate::redis::{instrumented, Backoff, Compression, Connector};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+/// The init script.
+/// It is used to store the computed value, if no other instance stored one before, and to release the init lock.
+/// Returns the stored value, so all instances get the same value.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the value,
+/// 2. The token of the init lock,
+/// 3. The computed value.
+const INIT_SCRIPT: &str = r#"
+redis.call("SET", ARGV[1], ARGV[3], "NX")
+if redis.call("GET", ARGV[1] .. ":init") == ARGV[2] then
+    redis.call("DEL", ARGV[1] .. ":init")
+end
+return redis.call("GET", ARGV[1])"#;
+
+/// A value, which is initialized only once across all instances, like [OnceCell::get_or_init](std::cell::OnceCell::get_or_init).
+///
+/// The first caller of [OnceValue::get_or_init] takes a short-lived init lock at `<key>:init` and computes the value.
+/// All concurrent callers wait until the value is stored and return the stored value,
+/// so an expensive initializer runs only once, even if many instances start at the same time.
+///
+/// If the initializer crashes, the init lock expires after the lease and the next caller computes the value.
+/// The default lease is 10 seconds, so use [OnceValue::with_lease], if the initializer needs more time.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::OnceValue;
+/// use std::thread;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// OnceValue::<String>::new("test_once_value_example", client.clone()).clear();
+///
+/// thread::scope(|s| {
+///     for i in 0..4 {
+///         let client = client.clone();
+///         s.spawn(move || {
+///             let mut config = OnceValue::new("test_once_value_example", client);
+///             // only one thread computes the value, all others get the same
+///             let value = config.get_or_init(|| format!("computed by {}", i)).clone();
+///             assert!(value.starts_with("computed by"));
+///         });
+///     }
+/// });
+/// ```
+pub struct OnceValue<T> {
+    key: String,
+    client: Connector,
+    lease: Duration,
+    backoff: Backoff,
+    cache: Option<T>,
+}
+
+impl<T> OnceValue<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new OnceValue.
+    /// An already stored value is kept and returned by [OnceValue::get_or_init] without initialization.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+            lease: Duration::from_secs(10),
+            backoff: Backoff::default(),
+            cache: None,
+        }
+    }
+
+    /// Sets the lease of the init lock.
+    /// If the initializer takes longer, another instance computes the value too,
+    /// but only the first stored value is kept and returned to all instances.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Sets the backoff between two attempts to get the value, while another instance initializes it.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Returns the stored value or initializes it with the given function.
+    ///
+    /// The function is only called, if no value is stored and this instance got the init lock.
+    /// Otherwise, it blocks until the value is stored by the instance, which holds the init lock.
+    pub fn get_or_init(&mut self, f: impl FnOnce() -> T) -> &T {
+        let mut f = Some(f);
+        let mut backoff = self.backoff.start();
+        loop {
+            if let Some(value) = self.load() {
+                return self.cache.insert(value);
+            }
+            if let Some(token) = self.try_lock() {
+                let init = f.take().expect("The initializer is only called once");
+                let value = self.init(token, init());
+                return self.cache.insert(value);
+            }
+            backoff.wait();
+        }
+    }
+
+    /// Returns the stored value without initializing it.
+    /// Returns None, if the value is not initialized yet.
+    pub fn get(&mut self) -> Option<&T> {
+        self.cache = self.load();
+        self.cache.as_ref()
+    }
+
+    /// Removes the stored value, so the next call of [OnceValue::get_or_init] initializes it again.
+    pub fn clear(&mut self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("clear", &self.key, || {
+            redis::Cmd::del(&self.key).query::<()>(&mut conn)
+        })
+        .expect("Failed to clear value");
+        self.cache = None;
+    }
+
+    fn load(&self) -> Option<T> {
+        let mut conn = self.client.get_connection().unwrap();
+        let res: Option<Vec<u8>> = instrumented("acquire", &self.key, || {
+            redis::Cmd::get(&self.key).query(&mut conn)
+        })
+        .expect("Failed to load value");
+        res.map(|v| decode(&v))
+    }
+
+    /// Takes the init lock with a new token.
+    /// Returns None, if another instance holds the lock.
+    fn try_lock(&self) -> Option<usize> {
+        let mut conn = self.client.get_connection().unwrap();
+        let token: usize = instrumented("uuid", &self.key, || {
+            redis::Cmd::incr(format!("{}:init_tokens", self.key), 1).query(&mut conn)
+        })
+        .expect("Failed to get token");
+        let millis = (self.lease.as_millis() as u64).max(1);
+        let locked: Option<String> = instrumented("lock", &self.key, || {
+            redis::cmd("SET")
+                .arg(format!("{}:init", self.key))
+                .arg(token)
+                .arg("NX")
+                .arg("PX")
+                .arg(millis)
+                .query(&mut conn)
+        })
+        .expect("Failed to lock value");
+        locked.map(|_| token)
+    }
+
+    /// Stores the computed value and releases the init lock.
+    /// Returns the stored value, which is another one, if the lock expired and another instance was faster.
+    fn init(&self, token: usize, value: T) -> T {
+        let mut conn = self.client.get_connection().unwrap();
+        let stored: Vec<u8> = instrumented("init", &self.key, || {
+            redis::Script::new(INIT_SCRIPT)
+                .arg(&self.key)
+                .arg(token)
+                .arg(encode(&value, Compression::Raw))
+                .invoke(&mut conn)
+        })
+        .expect("Failed to store value");
+        decode(&stored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn test_once_value() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut once: OnceValue<usize> = OnceValue::new("test_once_value", client.clone());
+        once.clear();
+        assert_eq!(once.get(), None);
+
+        let calls = AtomicUsize::new(0);
+        let values: Vec<usize> = thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|i| {
+                    let client = client.clone();
+                    let calls = &calls;
+                    s.spawn(move || {
+                        let mut once = OnceValue::new("test_once_value", client);
+                        *once.get_or_init(|| {
+                            calls.fetch_add(1, Ordering::Relaxed);
+                            thread::sleep(Duration::from_millis(100));
+                            i
+                        })
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        // the initializer ran once and everyone got its result
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert!(values.iter().all(|v| *v == values[0]));
+        assert_eq!(once.get(), Some(&values[0]));
+        once.clear();
+    }
+}
@@ -0,0 +1,115 @@
+//! # Periodic Load
+//! This module contains the periodic load type, which keeps a value fresh in the background.
+use crate::redis::Generic;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// A wrapper, which loads the value of a [Generic] periodically in a background thread.
+///
+/// The latest loaded value can be read with [PeriodicLoad::latest] without waiting on Redis.
+/// So it suits configs or dashboards, which should reflect the approximately current state.
+/// If a load fails, the last known value is kept.
+///
+/// The background thread is stopped, when the PeriodicLoad is dropped.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::{Di32, PeriodicLoad};
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let i32 = Di32::with_value(1, "test_periodic_load_example", client);
+/// let periodic = PeriodicLoad::new(i32, Duration::from_millis(100));
+/// assert_eq!(periodic.latest(), 1);
+/// periodic.stop();
+/// ```
+pub struct PeriodicLoad<T> {
+    latest: Arc<RwLock<T>>,
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<T> PeriodicLoad<T>
+where
+    T: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    /// Creates a new PeriodicLoad, which loads the value of the given type every interval.
+    ///
+    /// The value is loaded once before the background thread is started,
+    /// so it panics like [Generic::acquire], if there is no value stored in Redis.
+    pub fn new(mut data: Generic<T>, interval: Duration) -> Self {
+        let latest = Arc::new(RwLock::new(data.acquire().clone()));
+        let (stop, rx) = channel::<()>();
+
+        let shared = latest.clone();
+        let handle = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(interval) {
+                let Ok(conn) = data.client.get_read_connection() else {
+                    continue;
+                };
+                if let Some(value) = data.try_get(conn) {
+                    *shared.write().expect("Latest value is poisoned") = value;
+                }
+            }
+        });
+
+        Self {
+            latest,
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    /// Returns a clone of the latest loaded value.
+    /// It never waits on Redis.
+    pub fn latest(&self) -> T {
+        self.latest
+            .read()
+            .expect("Latest value is poisoned")
+            .clone()
+    }
+}
+
+impl<T> PeriodicLoad<T> {
+    /// Stops the background thread and waits until it has exited.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        // dropping the sender wakes up the thread
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl<T> Drop for PeriodicLoad<T> {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::Di32;
+
+    #[test]
+    fn test_periodic_load() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let i32 = Di32::with_value(1, "test_periodic_load", client.clone());
+        let periodic = PeriodicLoad::new(i32, Duration::from_millis(50));
+        assert_eq!(periodic.latest(), 1);
+
+        Di32::with_value(2, "test_periodic_load", client);
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(periodic.latest(), 2);
+        periodic.stop();
+    }
+}
@@ -0,0 +1,52 @@
+//! # Read Only
+//! This module contains the read-only view of a [Generic].
+use crate::redis::Generic;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A read-only view of a [Generic], which is created by [Generic::as_readonly].
+///
+/// It can only load the value, so a function, which gets the view, cannot store a value by accident.
+/// The loaded value is cached in the view, because the view cannot update the cache of the borrowed type.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::{Di32, ReadOnly};
+///
+/// fn observe(value: &mut ReadOnly<'_, i32>) -> i32 {
+///     *value.acquire()
+/// }
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let i32 = Di32::with_value(1, "test_readonly_example", client);
+/// assert_eq!(observe(&mut i32.as_readonly()), 1);
+/// ```
+pub struct ReadOnly<'a, T> {
+    data: &'a Generic<T>,
+    cache: Option<T>,
+}
+
+impl<'a, T> ReadOnly<'a, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    pub(crate) fn new(data: &'a Generic<T>) -> Self {
+        Self { data, cache: None }
+    }
+
+    /// Loads the value from Redis like [Generic::acquire].
+    /// If a replica is configured in the [Connector](crate::redis::Connector), it is loaded from the replica.
+    ///
+    /// # Panics
+    /// Panics, if there is no value stored in Redis.
+    pub fn acquire(&mut self) -> &T {
+        self.cache = self.data.try_get(self.data.get_read_conn());
+        self.cache.as_ref().expect("No value stored in Redis")
+    }
+
+    /// Returns the value, which was loaded last by this view, or the cached value of the type.
+    /// Returns None, if both are empty.
+    pub fn cached(&self) -> Option<&T> {
+        self.cache.as_ref().or(self.data.cached())
+    }
+}
@@ -0,0 +1,61 @@
+//! # Result Type
+//! This module contains the result type to publish the outcome of an operation.
+use crate::redis::Generic;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub type TResult<T, E> = Generic<Result<T, E>>;
+
+impl<T, E> TResult<T, E>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    E: Serialize + DeserializeOwned + Clone,
+{
+    /// Stores the successful result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::DResult;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut res: DResult<u32, String> = DResult::new("test_result_example", client.clone());
+    /// res.set_err("failed".to_string());
+    /// let mut consumer: DResult<u32, String> = DResult::new("test_result_example", client);
+    /// assert_eq!(consumer.get(), Some(Err("failed".to_string())));
+    /// ```
+    pub fn set_ok(&mut self, value: T) {
+        self.store(Ok(value));
+    }
+
+    /// Stores the failed result.
+    pub fn set_err(&mut self, err: E) {
+        self.store(Err(err));
+    }
+
+    /// Loads the result from Redis.
+    /// Returns None, if no result is published yet.
+    pub fn get(&mut self) -> Option<Result<T, E>> {
+        self.cache = self.try_get(self.get_read_conn());
+        self.cache.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut res: TResult<u32, String> = TResult::new("test_result", client.clone());
+        redis::cmd("DEL")
+            .arg("test_result")
+            .execute(&mut client.get_connection().unwrap());
+        assert_eq!(res.get(), None);
+        res.set_ok(1);
+        assert_eq!(res.get(), Some(Ok(1)));
+        res.set_err("error".to_string());
+        assert_eq!(res.get(), Some(Err("error".to_string())));
+    }
+}
@@ -0,0 +1,179 @@
+//! # RPC
+//! This module contains a request/response helper on top of the [Channel] type.
+use crate::redis::compression::encode;
+use crate::redis::{instrumented, Channel, Compression, Connector};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+use thiserror::Error;
+
+/// The time in seconds, how long a response is kept, if the client does not receive it anymore.
+const REPLY_TTL: usize = 60;
+
+#[derive(Error, Debug)]
+pub enum RpcError {
+    #[error("Timeout while waiting for the response")]
+    Timeout,
+    #[error("Error by Redis")]
+    Redis(#[from] redis::RedisError),
+}
+
+/// The key of the list, where the response to the request with the given id is pushed.
+fn reply_key(key: &str, id: u64) -> String {
+    format!("{}:reply:{}", key, id)
+}
+
+/// The client side of a request/response pair.
+///
+/// Every request gets a unique correlation id and is sent to the [Channel] at the key.
+/// Afterwards the client waits for the response at `<key>:reply:<id>`.
+/// So multiple clients can call multiple [RpcServer]s on the same key and every client receives its own response.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::{RpcClient, RpcServer};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut rpc: RpcClient<i32, i32> = RpcClient::new("test_rpc_example", client.clone());
+///
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         let mut server = RpcServer::new("test_rpc_example", client.clone());
+///         server.handle(Duration::from_secs(1), |req: i32| req * 2);
+///     });
+///     assert_eq!(rpc.call(21, Duration::from_secs(1)).unwrap(), 42);
+/// });
+/// ```
+pub struct RpcClient<Req, Resp> {
+    key: String,
+    client: Connector,
+    requests: Channel<(u64, Req)>,
+    _phantom: std::marker::PhantomData<Resp>,
+}
+
+impl<Req, Resp> RpcClient<Req, Resp>
+where
+    Req: Serialize + DeserializeOwned,
+    Resp: Serialize + DeserializeOwned,
+{
+    /// Creates a new RpcClient, which sends its requests to the servers at the key.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        let client = client.into();
+        Self {
+            key: key.to_string(),
+            requests: Channel::new(key, client.clone()),
+            client,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Sends the request and waits up to the timeout for the response.
+    ///
+    /// Returns [RpcError::Timeout], if no server responded in time.
+    /// The request is not withdrawn, so it can still be handled by a server later.
+    pub fn call(&mut self, req: Req, timeout: Duration) -> Result<Resp, RpcError> {
+        let mut conn = self.client.get_connection()?;
+        let id: u64 = instrumented("call", &self.key, || {
+            redis::Cmd::incr(format!("{}:ids", self.key), 1).query(&mut conn)
+        })?;
+
+        self.requests.send(&(id, req));
+        Channel::new(&reply_key(&self.key, id), self.client.clone())
+            .recv(timeout)
+            .ok_or(RpcError::Timeout)
+    }
+}
+
+/// The server side of a request/response pair. See [RpcClient] for an example.
+///
+/// Every request is handled by exactly one server, so multiple servers can share the load.
+pub struct RpcServer<Req, Resp> {
+    key: String,
+    client: Connector,
+    requests: Channel<(u64, Req)>,
+    _phantom: std::marker::PhantomData<Resp>,
+}
+
+impl<Req, Resp> RpcServer<Req, Resp>
+where
+    Req: Serialize + DeserializeOwned,
+    Resp: Serialize + DeserializeOwned,
+{
+    /// Creates a new RpcServer, which receives the requests of the clients at the key.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        let client = client.into();
+        Self {
+            key: key.to_string(),
+            requests: Channel::new(key, client.clone()),
+            client,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Waits up to the timeout for a request, handles it and sends the response to the client.
+    /// Returns false, if no request was received in time.
+    ///
+    /// The response expires after a minute, if the client does not receive it anymore.
+    pub fn handle(&mut self, timeout: Duration, handler: impl FnOnce(Req) -> Resp) -> bool {
+        let Some((id, req)) = self.requests.recv(timeout) else {
+            return false;
+        };
+
+        let resp = handler(req);
+        let reply_key = reply_key(&self.key, id);
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("handle", &self.key, || {
+            redis::pipe()
+                .atomic()
+                .rpush(&reply_key, encode(&resp, Compression::Raw))
+                .expire(&reply_key, REPLY_TTL)
+                .query::<()>(&mut conn)
+        })
+        .expect("Failed to send response");
+        true
+    }
+
+    /// Handles the requests in an endless loop with [RpcServer::handle].
+    pub fn serve(&mut self, mut handler: impl FnMut(Req) -> Resp) -> ! {
+        loop {
+            self.handle(Duration::from_secs(1), &mut handler);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_rpc() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut rpc: RpcClient<String, usize> = RpcClient::new("test_rpc", client.clone());
+        Channel::<(u64, String)>::new("test_rpc", client.clone()).clear();
+        assert!(matches!(
+            rpc.call("nobody".to_string(), Duration::from_millis(100)),
+            Err(RpcError::Timeout)
+        ));
+        Channel::<(u64, String)>::new("test_rpc", client.clone()).clear();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                let mut server = RpcServer::new("test_rpc", client.clone());
+                for _ in 0..2 {
+                    assert!(server.handle(Duration::from_secs(1), |req: String| req.len()));
+                }
+            });
+            assert_eq!(
+                rpc.call("a".to_string(), Duration::from_secs(1)).unwrap(),
+                1
+            );
+            assert_eq!(
+                rpc.call("abc".to_string(), Duration::from_secs(1)).unwrap(),
+                3
+            );
+        });
+    }
+}
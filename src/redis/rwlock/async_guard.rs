@@ -0,0 +1,190 @@
+use super::lock::RwLock;
+use crate::redis::rwlock::constants::{
+    LOAD_SCRIPT, READER_LOCK_DROP, STORE_SCRIPT, WRITER_LOCK_DROP,
+};
+use crate::redis::rwlock::RwLockError;
+use crate::redis::{instrumented_async, release_in_background};
+use crate::redis::{Error, Generic, LockError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ops::Deref;
+
+/// Loads the value with the load script, if the uuid holds the lock.
+/// The value is decoded with the encoding of the guarded type.
+async fn load<T: Serialize + DeserializeOwned>(
+    conn: &mut redis::aio::Connection,
+    data: &Generic<T>,
+    separator: &str,
+    uuid: usize,
+) -> Result<Option<T>, Error> {
+    let result: Option<Vec<u8>> = instrumented_async(
+        "acquire",
+        &data.key,
+        redis::Script::new(LOAD_SCRIPT)
+            .arg(&data.key)
+            .arg(uuid)
+            .arg(separator)
+            .invoke_async(conn),
+    )
+    .await?;
+    Ok(result.map(|v| data.decode_value(&v)))
+}
+
+/// The guard of [RwLock::read_async].
+/// It has to be released with [AsyncRwLockReadGuard::release], because there is no async drop.
+pub struct AsyncRwLockReadGuard<'a, T> {
+    lock: &'a RwLock<T>,
+    uuid: usize,
+    conn: Option<redis::aio::Connection>,
+    cache: Option<T>,
+}
+
+impl<'a, T> AsyncRwLockReadGuard<'a, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    pub(crate) fn new(lock: &'a RwLock<T>, uuid: usize, conn: redis::aio::Connection) -> Self {
+        Self {
+            lock,
+            uuid,
+            conn: Some(conn),
+            cache: None,
+        }
+    }
+
+    /// Loads the value from Redis like [RwLockReadGuard::acquire](super::RwLockReadGuard::acquire).
+    pub async fn acquire(&mut self) -> Result<&T, Error> {
+        let conn = self.conn.as_mut().ok_or(LockError::NoConnection)?;
+        self.cache = load(conn, &self.lock.data, &self.lock.separator, self.uuid).await?;
+        Ok(self.cache.as_ref().unwrap())
+    }
+
+    /// Releases the reader lock in Redis.
+    pub async fn release(mut self) -> Result<(), RwLockError> {
+        let Some(mut conn) = self.conn.take() else {
+            return Ok(());
+        };
+        instrumented_async(
+            "unlock",
+            &self.lock.data.key,
+            redis::Script::new(READER_LOCK_DROP)
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(&self.lock.separator)
+                .invoke_async::<_, ()>(&mut conn),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+impl<T> Deref for AsyncRwLockReadGuard<'_, T> {
+    type Target = Generic<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.lock.data
+    }
+}
+
+impl<T> Drop for AsyncRwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            release_in_background(
+                conn,
+                READER_LOCK_DROP,
+                self.lock.data.key.clone(),
+                self.uuid,
+                self.lock.separator.clone(),
+            );
+        }
+    }
+}
+
+/// The guard of [RwLock::write_async].
+/// It has to be released with [AsyncRwLockWriteGuard::release], because there is no async drop.
+pub struct AsyncRwLockWriteGuard<'a, T> {
+    lock: &'a mut RwLock<T>,
+    uuid: usize,
+    conn: Option<redis::aio::Connection>,
+}
+
+impl<'a, T> AsyncRwLockWriteGuard<'a, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    pub(crate) fn new(lock: &'a mut RwLock<T>, uuid: usize, conn: redis::aio::Connection) -> Self {
+        Self {
+            lock,
+            uuid,
+            conn: Some(conn),
+        }
+    }
+
+    /// Stores the value in Redis like [RwLockWriteGuard::store](super::RwLockWriteGuard::store).
+    pub async fn store(&mut self, value: T) -> Result<(), RwLockError> {
+        let conn = self.conn.as_mut().expect("Connection should be there");
+        let result: i8 = instrumented_async(
+            "store",
+            &self.lock.data.key,
+            redis::Script::new(STORE_SCRIPT)
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(self.lock.data.encode_value(&value))
+                .arg(&self.lock.separator)
+                .invoke_async(conn),
+        )
+        .await?;
+        if result == 0 {
+            return Err(RwLockError::LockExpired(self.uuid));
+        }
+        self.lock.data.cache = Some(value);
+        Ok(())
+    }
+
+    /// Loads the value from Redis like [RwLockWriteGuard::acquire](super::RwLockWriteGuard::acquire).
+    pub async fn acquire(&mut self) -> Result<&T, Error> {
+        let conn = self.conn.as_mut().ok_or(LockError::NoConnection)?;
+        self.lock.data.cache = load(conn, &self.lock.data, &self.lock.separator, self.uuid).await?;
+        Ok(self.lock.data.cache.as_ref().unwrap())
+    }
+
+    /// Releases the writer lock in Redis.
+    pub async fn release(mut self) -> Result<(), RwLockError> {
+        let Some(mut conn) = self.conn.take() else {
+            return Ok(());
+        };
+        instrumented_async(
+            "unlock",
+            &self.lock.data.key,
+            redis::Script::new(WRITER_LOCK_DROP)
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(&self.lock.separator)
+                .invoke_async::<_, ()>(&mut conn),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+impl<T> Deref for AsyncRwLockWriteGuard<'_, T> {
+    type Target = Generic<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.lock.data
+    }
+}
+
+impl<T> Drop for AsyncRwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            release_in_background(
+                conn,
+                WRITER_LOCK_DROP,
+                self.lock.data.key.clone(),
+                self.uuid,
+                self.lock.separator.clone(),
+            );
+        }
+    }
+}
@@ -1,24 +1,35 @@
+use std::time::Duration;
+
+/// The default lease of the reader and writer locks.
+/// A lock, which is not renewed or dropped in time, expires, so a crashed instance cannot block the lock forever.
+pub const LEASE: Duration = Duration::from_secs(2);
+
 /// The read lock script.
 ///
-/// Checks if the writer list besides the key is empty or the lock is set.
-/// If there are no writers, the uuid will be set as a reader and returns true.
+/// Checks if there is no writer lock and no writer is waiting for it.
+/// The readers and the waiting writers are stored in sorted sets besides the key with their expiry time as score,
+/// so expired entries can be removed and counted without scanning the keyspace.
+/// If there are no writers, the uuid will be added as a reader and returns true.
 /// Returns false otherwise.
 ///
 /// The timeout will be used for the reader lock. You need to retry to get the lock again if you want to keep it.
 /// But if a writer comes in scope, the reader lock will be dropped after the timeout and you have to wait.
 ///
-/// Takes 3 arguments:
+/// Takes 4 arguments:
 /// 1. The key to lock
 /// 2. The uuid of the lock
-/// 3. The timeout in seconds
+/// 3. The timeout in milliseconds
+/// 4. The separator of the derived keys
 pub const READER_LOCK: &str = r#"
-if redis.call("exists", ARGV[1] .. ":lock") == 1 then
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+if redis.call("exists", ARGV[1] .. ARGV[4] .. "lock") == 1 then
     return 0
 end
 
-local res = redis.call("scan", 0, "match", ARGV[1] .. ":writer_waiting_list:*")
-if next(res[2]) == nil then
-    redis.call("set", ARGV[1] .. ":reader_locks:" .. ARGV[2], 1, "ex", ARGV[3])
+redis.call("zremrangebyscore", ARGV[1] .. ARGV[4] .. "writer_waiting_list", "-inf", now)
+if redis.call("zcard", ARGV[1] .. ARGV[4] .. "writer_waiting_list") == 0 then
+    redis.call("zadd", ARGV[1] .. ARGV[4] .. "reader_locks", now + ARGV[3], ARGV[2])
     return 1
 end
 return 0
@@ -28,49 +39,102 @@ return 0
 ///
 /// Removes the uuid from the reader list.
 ///
-/// Takes 2 arguments:
+/// Takes 3 arguments:
 /// 1. The key to lock
 /// 2. The uuid of the lock
+/// 3. The separator of the derived keys
 pub const READER_LOCK_DROP: &str = r#"
-redis.call("del", ARGV[1] .. ":reader_locks:" .. ARGV[2])
+redis.call("zrem", ARGV[1] .. ARGV[3] .. "reader_locks", ARGV[2])
 return 1
 "#;
 
 /// The writer lock script.
 ///
-/// Checks if the reader list besides the key is empty.
-/// Also add the uuid to the writer waiting list.
-/// If there are no readers, the uuid will be set as the lock and returns true.
+/// Adds the uuid to the writer waiting list first, so no new reader can get the lock anymore.
+/// Checks if there is no other writer lock and the reader list is empty.
+/// If so, the uuid will be set as the lock, it is removed from the waiting list and returns true.
 /// Returns false otherwise.
 ///
+/// So the readers, which hold the lock already, can finish, but a continuous stream of new readers
+/// cannot starve the writer.
+///
 /// The timeout will also be used for the waiting ticket, so if you wait too long, your intention will be dropped and reader can be acquired.
 /// So be sure to request the lock again fast enough.
 ///
-/// Takes 3 arguments:
+/// Takes 4 arguments:
 /// 1. The key to lock
 /// 2. The uuid of the lock
-/// 3. The timeout in seconds for waiting
-// TODO: Should lock be expanded, if there is already another writer waiting?
+/// 3. The timeout in milliseconds for waiting
+/// 4. The separator of the derived keys
 pub const WRITER_LOCK: &str = r#"
-redis.call("set", ARGV[1] .. ":writer_waiting_list:" .. ARGV[2], 1, "ex", ARGV[3])
-if redis.call("exists", ARGV[1] .. ":lock") == 1 then
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+redis.call("zadd", ARGV[1] .. ARGV[4] .. "writer_waiting_list", now + ARGV[3], ARGV[2])
+if redis.call("exists", ARGV[1] .. ARGV[4] .. "lock") == 1 then
     return 0
 end
 
-return redis.call("set", ARGV[1] .. ":lock", ARGV[2], "nx", "ex", ARGV[3])
+redis.call("zremrangebyscore", ARGV[1] .. ARGV[4] .. "reader_locks", "-inf", now)
+if redis.call("zcard", ARGV[1] .. ARGV[4] .. "reader_locks") > 0 then
+    return 0
+end
+
+redis.call("set", ARGV[1] .. ARGV[4] .. "lock", ARGV[2], "px", ARGV[3])
+redis.call("zrem", ARGV[1] .. ARGV[4] .. "writer_waiting_list", ARGV[2])
+return 1
+"#;
+
+/// The read lock renew script.
+///
+/// Extends the expiry of the uuid in the reader list, only if it is not expired yet.
+/// Returns true, if the lease was extended.
+///
+/// Takes 4 arguments:
+/// 1. The key to lock
+/// 2. The uuid of the lock
+/// 3. The timeout in milliseconds
+/// 4. The separator of the derived keys
+pub const READER_RENEW: &str = r#"
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+local expires = redis.call("zscore", ARGV[1] .. ARGV[4] .. "reader_locks", ARGV[2])
+if expires and tonumber(expires) > now then
+    redis.call("zadd", ARGV[1] .. ARGV[4] .. "reader_locks", "XX", now + ARGV[3], ARGV[2])
+    return 1
+end
+return 0
+"#;
+
+/// The writer lock renew script.
+///
+/// Extends the expiry of the lock, only if the uuid still holds it.
+/// Returns true, if the lease was extended.
+///
+/// Takes 4 arguments:
+/// 1. The key to lock
+/// 2. The uuid of the lock
+/// 3. The timeout in milliseconds
+/// 4. The separator of the derived keys
+pub const WRITER_RENEW: &str = r#"
+if redis.call("get", ARGV[1] .. ARGV[4] .. "lock") == ARGV[2] then
+    redis.call("pexpire", ARGV[1] .. ARGV[4] .. "lock", ARGV[3])
+    return 1
+end
+return 0
 "#;
 
 /// The writer lock drop script.
 ///
 /// Removes the uuid from the writer list.
 ///
-/// Takes 2 arguments:
+/// Takes 3 arguments:
 /// 1. The key to lock
 /// 2. The uuid of the lock
+/// 3. The separator of the derived keys
 pub const WRITER_LOCK_DROP: &str = r#"
-redis.call("del", ARGV[1] .. ":writer_waiting_list:" .. ARGV[2])
-if redis.call("get", ARGV[1] .. ":lock") == ARGV[2] then
-    redis.call("del", ARGV[1] .. ":lock")
+redis.call("zrem", ARGV[1] .. ARGV[3] .. "writer_waiting_list", ARGV[2])
+if redis.call("get", ARGV[1] .. ARGV[3] .. "lock") == ARGV[2] then
+    redis.call("del", ARGV[1] .. ARGV[3] .. "lock")
 end
 return 1
 "#;
@@ -79,24 +143,26 @@ return 1
 ///
 /// Increments the uuid counter and returns the new value.
 ///
-/// Takes 1 argument:
+/// Takes 2 arguments:
 /// 1. The key to lock
+/// 2. The separator of the derived keys
 pub const UUID_SCRIPT: &str = r#"
-return redis.call("INCR", ARGV[1] .. ":lock_counter")
+return redis.call("INCR", ARGV[1] .. ARGV[2] .. "lock_counter")
 "#;
 
 /// The read script.
 ///
 /// Reads the value from the key, only if the uuid is in reader list or if the lock is equal to uuid.
 ///
-/// Takes 2 argument:
+/// Takes 3 arguments:
 /// 1. The key to read
 /// 2. The uuid of the lock
+/// 3. The separator of the derived keys
 pub const LOAD_SCRIPT: &str = r#"
-if redis.call("get", ARGV[1] .. ":lock") == ARGV[2] then
+if redis.call("get", ARGV[1] .. ARGV[3] .. "lock") == ARGV[2] then
     return redis.call("get", ARGV[1])
 end
-if redis.call("exists", ARGV[1] .. ":reader_locks:" .. ARGV[2]) then
+if redis.call("zscore", ARGV[1] .. ARGV[3] .. "reader_locks", ARGV[2]) then
     return redis.call("get", ARGV[1])
 end
 "#;
@@ -105,12 +171,13 @@ end
 ///
 /// Stores the value to the key, only if the uuid is in lock.
 ///
-/// Takes 3 arguments:
+/// Takes 4 arguments:
 /// 1. The key to store
 /// 2. The uuid of the lock
 /// 3. The value to store
+/// 4. The separator of the derived keys
 pub const STORE_SCRIPT: &str = r#"
-if redis.call("get", ARGV[1] .. ":lock") == ARGV[2] then
+if redis.call("get", ARGV[1] .. ARGV[4] .. "lock") == ARGV[2] then
     redis.call("set", ARGV[1], ARGV[3])
     return 1
 end
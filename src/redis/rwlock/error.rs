@@ -9,4 +9,6 @@ pub enum RwLockError {
     LockNotDroppable,
     #[error("The lock is expired. Failed UUID: {0} ")]
     LockExpired(usize),
+    #[error("Error by Redis")]
+    Redis(#[from] redis::RedisError),
 }
@@ -1,11 +1,16 @@
 use super::RwLockReadGuard;
 use super::RwLockWriteGuard;
-use crate::redis::rwlock::constants::{READER_LOCK, UUID_SCRIPT, WRITER_LOCK};
-use crate::redis::{Generic, LockError};
-use redis::Connection;
+#[cfg(feature = "async")]
+use super::{AsyncRwLockReadGuard, AsyncRwLockWriteGuard};
+#[cfg(feature = "async")]
+use crate::redis::instrumented_async;
+use crate::redis::rwlock::constants::{LEASE, READER_LOCK, UUID_SCRIPT, WRITER_LOCK};
+use crate::redis::{instrumented, Backoff, ConnectionGuard, Generic, LockError, DEFAULT_SEPARATOR};
+use redis::RedisResult;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
 
 /// A Read-Write Lock.
 ///
@@ -17,6 +22,18 @@ use std::ops::{Deref, DerefMut};
 /// If you try to get a writer lock in a thread, which already has a reader lock, you will end up in a deadlock.
 /// To use the RwLock in threads, you need a scoped thread.
 ///  
+/// # Lease
+///
+/// The reader and writer locks expire after 2000ms, so a crashed instance cannot block the lock forever.
+/// The lease can be changed with [RwLock::with_lease].
+/// If you hold a lock for a longer time, renew it with [RwLockReadGuard::renew] and [RwLockWriteGuard::renew]
+/// or let a background thread do it with `auto_renew`.
+///
+/// # Keys
+///
+/// The locks are stored in derived keys like `<key>:lock` and `<key>:reader_locks`.
+/// If `:` clashes with the naming convention of your keys, use [RwLock::with_separator].
+///
 /// # Examples
 ///
 /// ## Linear usage
@@ -75,7 +92,9 @@ use std::ops::{Deref, DerefMut};
 /// ```
 pub struct RwLock<T> {
     pub(crate) data: Generic<T>,
-    pub(crate) conn: Option<redis::Connection>,
+    pub(crate) separator: String,
+    pub(crate) lease: Duration,
+    backoff: Backoff,
 }
 
 impl<T> RwLock<T>
@@ -83,7 +102,48 @@ where
     T: Serialize + DeserializeOwned,
 {
     pub fn new(data: Generic<T>) -> Self {
-        Self { data, conn: None }
+        Self {
+            data,
+            separator: DEFAULT_SEPARATOR.to_string(),
+            lease: LEASE,
+            backoff: Backoff::default(),
+        }
+    }
+
+    /// Sets the separator between the key and the suffixes of the derived keys, e.g. `<key>/lock` for `/`.
+    /// The default is `:`.
+    ///
+    /// All instances, which lock the same value, have to use the same separator.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::{Di32, RwLock};
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let lock = RwLock::new(Di32::with_value(1, "test_rwlock_separator_example", client))
+    ///     .with_separator("/");
+    /// assert_eq!(*lock.read().unwrap(), 1);
+    /// ```
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Sets the lease of the reader and writer locks. The default is 2000ms.
+    ///
+    /// All instances, which lock the same value, should use the same lease,
+    /// because a waiting writer keeps its priority only for the lease.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Sets the backoff between two attempts to acquire a reader or writer lock.
+    ///
+    /// The maximum backoff must be lower than the lease, otherwise a waiting writer loses its priority.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
     }
 
     /// Creates a new RwLock Reader.
@@ -91,43 +151,213 @@ where
     /// This function blocks until the lock is acquired.
     /// If there is a writer lock, this function blocks until the writer lock is dropped.
     /// Also if there is a writer locks waiting to be acquired, this function blocks until the writer lock is acquired and dropped.
-    pub fn read(&self) -> Result<RwLockReadGuard<T>, LockError> {
-        let mut conn = self.client.clone().get_connection().unwrap();
-        let uuid = self.acquire_via_script(READER_LOCK, &mut conn);
+    pub fn read(&self) -> Result<RwLockReadGuard<'_, T>, LockError> {
+        let mut conn = self
+            .client
+            .get_connection()
+            .map_err(|_| LockError::NoConnection)?;
+        let uuid = self.acquire_via_script("read", READER_LOCK, &mut conn, None)?;
         Ok(RwLockReadGuard::new(self, uuid, conn))
     }
 
+    /// Creates a new RwLock Reader like [RwLock::read], but stops trying after the timeout.
+    /// Returns None, if the lock could not be acquired in time or there is no connection to Redis.
+    ///
+    /// So a service can fall back to a cached value instead of blocking, while a writer holds the lock.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::{Di32, RwLock};
+    /// use std::time::Duration;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let lock = RwLock::new(Di32::with_value(1, "test_rwlock_try_read_for_example", client));
+    /// let value = match lock.try_read_for(Duration::from_millis(100)) {
+    ///     Some(mut read) => read.acquire().map(|v| *v).unwrap_or(0),
+    ///     None => 0, // degrade gracefully
+    /// };
+    /// assert_eq!(value, 1);
+    /// ```
+    pub fn try_read_for(&self, timeout: Duration) -> Option<RwLockReadGuard<'_, T>> {
+        let mut conn = self.client.get_connection().ok()?;
+        let deadline = Instant::now() + timeout;
+        let uuid = self
+            .acquire_via_script("read", READER_LOCK, &mut conn, Some(deadline))
+            .ok()?;
+        Some(RwLockReadGuard::new(self, uuid, conn))
+    }
+
     /// Creates a new RwLock Writer.
     ///
     /// This function blocks until the lock is acquired.
     /// If there is a reader lock, this function blocks until the reader lock is dropped.
-    /// The acquiring writer lock has priority over any waiting reader lock.
-    pub fn write(&mut self) -> Result<RwLockWriteGuard<T>, LockError> {
-        let mut conn = self.client.clone().get_connection().unwrap();
-        let uuid = self.acquire_via_script(WRITER_LOCK, &mut conn);
+    /// The acquiring writer lock has priority over any waiting reader lock,
+    /// so new readers are blocked until the writer got and dropped the lock.
+    pub fn write(&mut self) -> Result<RwLockWriteGuard<'_, T>, LockError> {
+        let mut conn = self
+            .client
+            .get_connection()
+            .map_err(|_| LockError::NoConnection)?;
+        let uuid = self.acquire_via_script("write", WRITER_LOCK, &mut conn, None)?;
         Ok(RwLockWriteGuard::new(self, uuid, conn))
     }
 
-    fn acquire_via_script(&self, script: &str, conn: &mut Connection) -> usize {
-        let uuid = self.generate_uuid(conn);
-        let mut res = false;
+    /// Creates a new RwLock Writer like [RwLock::write], but stops trying after the timeout.
+    /// Returns None, if the lock could not be acquired in time or there is no connection to Redis.
+    ///
+    /// If it gives up, the writer is removed from the waiting list, so it does not block new readers anymore.
+    pub fn try_write_for(&mut self, timeout: Duration) -> Option<RwLockWriteGuard<'_, T>> {
+        let mut conn = self.client.get_connection().ok()?;
+        let deadline = Instant::now() + timeout;
+        let uuid = self
+            .acquire_via_script("write", WRITER_LOCK, &mut conn, Some(deadline))
+            .ok()?;
+        Some(RwLockWriteGuard::new(self, uuid, conn))
+    }
+
+    /// Creates a new RwLock Reader like [RwLock::read], but waits asynchronously between the attempts.
+    /// Only available with the feature `async`.
+    ///
+    /// There is no async drop, so the returned guard has to be released with [AsyncRwLockReadGuard::release].
+    /// If it is dropped without release, the lock is released in a background task of the current tokio runtime.
+    /// Outside of a runtime, the lock lingers until its lease expires.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::{Di32, RwLock};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut lock = RwLock::new(Di32::with_value(1, "test_rwlock_async_example", client));
+    /// let mut read = lock.read_async().await.unwrap();
+    /// assert_eq!(read.acquire().await.unwrap(), &1);
+    /// read.release().await.unwrap();
+    ///
+    /// let mut write = lock.write_async().await.unwrap();
+    /// write.store(2).await.unwrap();
+    /// write.release().await.unwrap();
+    /// # });
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn read_async(&self) -> Result<AsyncRwLockReadGuard<'_, T>, LockError> {
+        let mut conn = self
+            .client
+            .get_async_connection()
+            .await
+            .map_err(|_| LockError::NoConnection)?;
+        let uuid = self
+            .acquire_via_script_async("read", READER_LOCK, &mut conn)
+            .await?;
+        Ok(AsyncRwLockReadGuard::new(self, uuid, conn))
+    }
+
+    /// Creates a new RwLock Writer like [RwLock::write], but waits asynchronously between the attempts.
+    /// Only available with the feature `async`.
+    ///
+    /// The returned guard has to be released with [AsyncRwLockWriteGuard::release] like the guard of [RwLock::read_async].
+    #[cfg(feature = "async")]
+    pub async fn write_async(&mut self) -> Result<AsyncRwLockWriteGuard<'_, T>, LockError> {
+        let mut conn = self
+            .client
+            .get_async_connection()
+            .await
+            .map_err(|_| LockError::NoConnection)?;
+        let uuid = self
+            .acquire_via_script_async("write", WRITER_LOCK, &mut conn)
+            .await?;
+        Ok(AsyncRwLockWriteGuard::new(self, uuid, conn))
+    }
 
-        while !res {
-            res = redis::Script::new(script)
+    #[cfg(feature = "async")]
+    async fn acquire_via_script_async(
+        &self,
+        op: &str,
+        script: &str,
+        conn: &mut redis::aio::Connection,
+    ) -> Result<usize, LockError> {
+        let uuid: usize = instrumented_async(
+            "uuid",
+            &self.data.key,
+            redis::Script::new(UUID_SCRIPT)
                 .arg(&self.data.key)
-                .arg(uuid)
-                .arg(2)
-                .invoke(conn)
-                .unwrap();
+                .arg(&self.separator)
+                .invoke_async(conn),
+        )
+        .await?;
+        let script = redis::Script::new(script);
+        let mut backoff = self.backoff.start();
+
+        loop {
+            let res: bool = instrumented_async(
+                op,
+                &self.data.key,
+                script
+                    .arg(&self.data.key)
+                    .arg(uuid)
+                    .arg(self.lease.as_millis() as u64)
+                    .arg(&self.separator)
+                    .invoke_async(conn),
+            )
+            .await?;
+            if res {
+                return Ok(uuid);
+            }
+            tokio::time::sleep(backoff.next_delay()).await;
+        }
+    }
+
+    /// Runs the lock script until it succeeds.
+    /// Returns [LockError::LockFailed], if the deadline is reached before.
+    fn acquire_via_script(
+        &self,
+        op: &str,
+        script: &str,
+        conn: &mut ConnectionGuard,
+        deadline: Option<Instant>,
+    ) -> Result<usize, LockError> {
+        let uuid = self.generate_uuid(conn)?;
+        let mut backoff = self.backoff.start();
+
+        loop {
+            let res: bool = instrumented(op, &self.data.key, || {
+                redis::Script::new(script)
+                    .arg(&self.data.key)
+                    .arg(uuid)
+                    .arg(self.lease.as_millis() as u64)
+                    .arg(&self.separator)
+                    .invoke(conn)
+            })?;
+            if res {
+                return Ok(uuid);
+            }
+            match deadline {
+                Some(deadline) => {
+                    if !backoff.wait_until(deadline) {
+                        self.abort(uuid, conn)?;
+                        return Err(LockError::LockFailed);
+                    }
+                }
+                None => backoff.wait(),
+            }
         }
-        uuid
     }
 
-    pub(crate) fn generate_uuid(&self, conn: &mut Connection) -> usize {
-        redis::Script::new(UUID_SCRIPT)
-            .arg(&self.data.key)
-            .invoke(conn)
-            .unwrap()
+    /// Removes the uuid from the writer waiting list, because it gave up to acquire the lock.
+    /// Readers are never in the list, so it is a no-op for them.
+    fn abort(&self, uuid: usize, conn: &mut ConnectionGuard) -> RedisResult<()> {
+        let waiting = format!("{}{}writer_waiting_list", self.data.key, self.separator);
+        instrumented("abort", &self.data.key, || {
+            redis::Cmd::zrem(&waiting, uuid).query(conn)
+        })
+    }
+
+    pub(crate) fn generate_uuid(&self, conn: &mut ConnectionGuard) -> RedisResult<usize> {
+        instrumented("uuid", &self.data.key, || {
+            redis::Script::new(UUID_SCRIPT)
+                .arg(&self.data.key)
+                .arg(&self.separator)
+                .invoke(conn)
+        })
     }
 }
 
@@ -150,6 +380,9 @@ mod tests {
     use super::*;
     use crate::redis::*;
     use std::mem::ManuallyDrop;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn test_rwlock() {
@@ -195,4 +428,115 @@ mod tests {
             let _ = lock.read().unwrap();
         }
     }
+
+    #[test]
+    fn test_rwlock_writer_not_starved() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let lease = Duration::from_millis(500);
+        let i32 = Di32::with_value(1, "test_rwlock_starvation", client.clone());
+        let mut lock = RwLock::new(i32).with_lease(lease);
+        let done = AtomicBool::new(false);
+
+        let elapsed = thread::scope(|s| {
+            // the readers overlap, so there is always a reader lock held
+            for _ in 0..3 {
+                s.spawn(|| {
+                    let lock = RwLock::new(Di32::new("test_rwlock_starvation", client.clone()))
+                        .with_lease(lease);
+                    while !done.load(Ordering::Relaxed) {
+                        let _read = lock.read().unwrap();
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                });
+            }
+            thread::sleep(Duration::from_millis(200));
+
+            let start = Instant::now();
+            let mut write = lock.write().unwrap();
+            write.store(2).unwrap();
+            drop(write);
+            done.store(true, Ordering::Relaxed);
+            start.elapsed()
+        });
+        // the writer only waits for the readers, which held the lock already
+        assert!(elapsed < lease, "{:?}", elapsed);
+        assert_eq!(*lock.read().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_rwlock_nil_string() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let s = DString::with_value("nil".to_string(), "test_rwlock_nil_string", client);
+        let mut lock = RwLock::new(s);
+        {
+            let mut read = lock.read().unwrap();
+            assert_eq!(read.acquire().unwrap(), "nil");
+        }
+        let mut write = lock.write().unwrap();
+        write.store("nil".to_string()).unwrap();
+        assert_eq!(write.acquire().unwrap(), "nil");
+    }
+
+    #[test]
+    fn test_rwlock_renew() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let i32 = Di32::with_value(1, "test_rwlock_renew", client.clone());
+        let mut lock = RwLock::new(i32).with_lease(Duration::from_millis(300));
+        {
+            let mut read = lock.read().unwrap();
+            thread::sleep(Duration::from_millis(200));
+            read.renew().unwrap();
+            thread::sleep(Duration::from_millis(400));
+            assert!(matches!(read.renew(), Err(RwLockError::LockExpired(_))));
+        }
+        {
+            // the lease would expire without the background renewal
+            let mut write = lock.write().unwrap();
+            write.auto_renew(Duration::from_millis(100));
+            thread::sleep(Duration::from_millis(500));
+            write.renew().unwrap();
+            write.store(2).unwrap();
+        }
+        assert_eq!(*lock.read().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_rwlock_try_for() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let i32 = Di32::with_value(1, "test_rwlock_try_for", client.clone());
+        let mut lock = RwLock::new(i32);
+        let mut other = RwLock::new(Di32::new("test_rwlock_try_for", client));
+
+        let read = lock.try_read_for(Duration::from_millis(100)).unwrap();
+        let start = Instant::now();
+        assert!(other.try_write_for(Duration::from_millis(200)).is_none());
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert!(start.elapsed() < Duration::from_millis(500));
+        drop(read);
+
+        // the writer, which gave up, does not block new readers
+        assert!(lock.try_read_for(Duration::from_millis(50)).is_some());
+        assert!(lock.try_write_for(Duration::from_millis(100)).is_some());
+    }
+
+    #[cfg(feature = "messagepack")]
+    #[test]
+    fn test_rwlock_messagepack() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let data: Generic<Vec<i32>> =
+            Generic::with_messagepack("test_rwlock_messagepack", client.clone());
+        let mut lock = RwLock::new(data);
+        {
+            let mut write = lock.write().unwrap();
+            write.store(vec![1, 2, 3]).unwrap();
+            assert_eq!(write.acquire().unwrap(), &vec![1, 2, 3]);
+        }
+        assert_eq!(lock.read().unwrap().acquire().unwrap(), &vec![1, 2, 3]);
+
+        let mut conn = client.get_connection().unwrap();
+        let raw: Vec<u8> = redis::Cmd::get("test_rwlock_messagepack")
+            .query(&mut conn)
+            .unwrap();
+        assert_eq!(raw, [0x93, 0x01, 0x02, 0x03]);
+    }
 }
@@ -1,9 +1,14 @@
+#[cfg(feature = "async")]
+mod async_guard;
 mod constants;
 mod error;
 mod lock;
 mod reader;
+mod renewer;
 mod writer;
 
+#[cfg(feature = "async")]
+pub use async_guard::{AsyncRwLockReadGuard, AsyncRwLockWriteGuard};
 pub use error::RwLockError;
 pub use lock::RwLock;
 pub use reader::RwLockReadGuard;
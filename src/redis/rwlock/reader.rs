@@ -1,51 +1,106 @@
 use super::lock::RwLock;
-use crate::redis::rwlock::constants::{LOAD_SCRIPT, READER_LOCK_DROP};
+use super::renewer::Renewer;
+use crate::redis::rwlock::constants::{LOAD_SCRIPT, READER_LOCK_DROP, READER_RENEW};
+use crate::redis::rwlock::RwLockError;
 use crate::redis::Generic;
+use crate::redis::{instrumented, ConnectionGuard, Error};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::ops::Deref;
+use std::time::Duration;
 
 pub struct RwLockReadGuard<'a, T> {
     lock: &'a RwLock<T>,
     uuid: usize,
-    conn: redis::Connection,
+    conn: ConnectionGuard,
     cache: Option<T>,
+    renewer: Option<Renewer>,
 }
 
 impl<'a, T> RwLockReadGuard<'a, T>
 where
     T: Serialize + DeserializeOwned,
 {
-    pub(crate) fn new(lock: &'a RwLock<T>, uuid: usize, conn: redis::Connection) -> Self {
+    pub(crate) fn new(lock: &'a RwLock<T>, uuid: usize, conn: ConnectionGuard) -> Self {
         Self {
             lock,
             uuid,
             conn,
             cache: None,
+            renewer: None,
         }
     }
 
+    /// Extends the lease of the reader lock by the lease of the [RwLock] from the point on its called.
+    /// Call it before the lease expires, if you need to read for a longer time,
+    /// otherwise a waiting writer can get the lock, while you are still reading.
+    ///
+    /// Returns [RwLockError::LockExpired], if the lease is already expired.
+    pub fn renew(&mut self) -> Result<(), RwLockError> {
+        let renewed: bool = instrumented("renew", &self.lock.data.key, || {
+            redis::Script::new(READER_RENEW)
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(self.lock.lease.as_millis() as u64)
+                .arg(&self.lock.separator)
+                .invoke(&mut self.conn)
+        })?;
+        if !renewed {
+            return Err(RwLockError::LockExpired(self.uuid));
+        }
+        Ok(())
+    }
+
+    /// Renews the lease every interval in a background thread with its own connection,
+    /// until the guard is dropped. The interval must be lower than the lease.
+    ///
+    /// It does not work with a [Connector](crate::redis::Connector) created from a single connection,
+    /// because the guard holds this connection already.
+    ///
+    /// # Example
+    /// ```
+    /// use dtypes::redis::{Di32, RwLock};
+    /// use std::time::Duration;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let lock = RwLock::new(Di32::with_value(1, "test_rwlock_auto_renew_example", client));
+    /// let mut read = lock.read().unwrap();
+    /// read.auto_renew(Duration::from_millis(500));
+    /// // a long read
+    /// assert_eq!(read.acquire().unwrap(), &1);
+    /// ```
+    pub fn auto_renew(&mut self, interval: Duration) {
+        self.renewer = Some(Renewer::start(
+            self.lock.data.client.clone(),
+            self.lock.data.key.clone(),
+            self.uuid,
+            self.lock.separator.clone(),
+            READER_RENEW,
+            self.lock.lease,
+            interval,
+        ));
+    }
+
     /// Loads the value from Redis.
     /// This function blocks until the value is loaded.
     /// Shadows the load operation of the guarded value.
-    pub fn acquire(&mut self) -> &T {
-        self.cache = self.try_get();
-        self.cache.as_ref().unwrap()
+    ///
+    /// Returns an [Error], if the value cannot be loaded from Redis.
+    pub fn acquire(&mut self) -> Result<&T, Error> {
+        self.cache = self.try_get()?;
+        Ok(self.cache.as_ref().unwrap())
     }
 
-    fn try_get(&mut self) -> Option<T> {
+    fn try_get(&mut self) -> Result<Option<T>, Error> {
         let script = redis::Script::new(LOAD_SCRIPT);
-        let result: Option<String> = script
-            .arg(&self.lock.data.key)
-            .arg(self.uuid)
-            .invoke(&mut self.conn)
-            .expect("Failed to load value. You should not see this!");
-        let result = result?;
-
-        if result == "nil" {
-            return None;
-        }
-        Some(serde_json::from_str(&result).expect("Failed to deserialize value"))
+        let result: Option<Vec<u8>> = instrumented("acquire", &self.lock.data.key, || {
+            script
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(&self.lock.separator)
+                .invoke(&mut self.conn)
+        })?;
+        Ok(result.map(|v| self.lock.data.decode_value(&v)))
     }
 }
 
@@ -59,11 +114,14 @@ impl<'a, T> Deref for RwLockReadGuard<'a, T> {
 
 impl<T> Drop for RwLockReadGuard<'_, T> {
     fn drop(&mut self) {
-        let mut conn = self.client.get_connection().unwrap();
-        let _: () = redis::Script::new(READER_LOCK_DROP)
-            .arg(&self.lock.data.key)
-            .arg(self.uuid)
-            .invoke(&mut conn)
-            .unwrap();
+        self.renewer.take();
+        // errors are ignored, because drop cannot return them. The lock expires after its lease instead.
+        let _ = instrumented("unlock", &self.lock.data.key, || {
+            redis::Script::new(READER_LOCK_DROP)
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(&self.lock.separator)
+                .invoke::<()>(&mut self.conn)
+        });
     }
 }
@@ -0,0 +1,61 @@
+use crate::redis::{instrumented, Connector};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Renews the lease of a reader or writer lock in a background thread.
+///
+/// The thread uses its own connection and is stopped, when the renewer is dropped.
+pub(crate) struct Renewer {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Renewer {
+    /// Starts the thread, which runs the renew script every interval.
+    /// The thread stops by itself, if the lock could not be renewed, because it is expired.
+    pub(crate) fn start(
+        client: Connector,
+        key: String,
+        uuid: usize,
+        separator: String,
+        script: &'static str,
+        lease: Duration,
+        interval: Duration,
+    ) -> Self {
+        let (stop, rx) = channel::<()>();
+        let handle = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(interval) {
+                let Ok(mut conn) = client.get_connection() else {
+                    continue;
+                };
+                let renewed: redis::RedisResult<bool> = instrumented("renew", &key, || {
+                    redis::Script::new(script)
+                        .arg(&key)
+                        .arg(uuid)
+                        .arg(lease.as_millis() as u64)
+                        .arg(&separator)
+                        .invoke(&mut conn)
+                });
+                if let Ok(false) = renewed {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Renewer {
+    fn drop(&mut self) {
+        // dropping the sender wakes up the thread
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -1,22 +1,68 @@
-use crate::redis::rwlock::constants::{LOAD_SCRIPT, STORE_SCRIPT, WRITER_LOCK_DROP};
+use super::renewer::Renewer;
+use crate::redis::rwlock::constants::{LOAD_SCRIPT, STORE_SCRIPT, WRITER_LOCK_DROP, WRITER_RENEW};
 use crate::redis::rwlock::RwLockError;
+use crate::redis::{instrumented, ConnectionGuard, Error};
 use crate::redis::{Generic, RwLock};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::ops::{Deref, DerefMut};
+use std::time::Duration;
 
 pub struct RwLockWriteGuard<'a, T> {
     lock: &'a mut RwLock<T>,
-    conn: redis::Connection,
+    conn: ConnectionGuard,
     uuid: usize,
+    renewer: Option<Renewer>,
 }
 
 impl<'a, T> RwLockWriteGuard<'a, T>
 where
     T: Serialize + DeserializeOwned,
 {
-    pub(crate) fn new(lock: &'a mut RwLock<T>, uuid: usize, conn: redis::Connection) -> Self {
-        Self { lock, uuid, conn }
+    pub(crate) fn new(lock: &'a mut RwLock<T>, uuid: usize, conn: ConnectionGuard) -> Self {
+        Self {
+            lock,
+            uuid,
+            conn,
+            renewer: None,
+        }
+    }
+
+    /// Extends the lease of the writer lock by the lease of the [RwLock] from the point on its called.
+    /// Call it before the lease expires, if you need the lock for a longer time,
+    /// otherwise another writer or the readers can get the lock.
+    ///
+    /// Returns [RwLockError::LockExpired], if the lease is already expired.
+    pub fn renew(&mut self) -> Result<(), RwLockError> {
+        let renewed: bool = instrumented("renew", &self.lock.data.key, || {
+            redis::Script::new(WRITER_RENEW)
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(self.lock.lease.as_millis() as u64)
+                .arg(&self.lock.separator)
+                .invoke(&mut self.conn)
+        })?;
+        if !renewed {
+            return Err(RwLockError::LockExpired(self.uuid));
+        }
+        Ok(())
+    }
+
+    /// Renews the lease every interval in a background thread with its own connection,
+    /// until the guard is dropped. The interval must be lower than the lease.
+    ///
+    /// It does not work with a [Connector](crate::redis::Connector) created from a single connection,
+    /// because the guard holds this connection already.
+    pub fn auto_renew(&mut self, interval: Duration) {
+        self.renewer = Some(Renewer::start(
+            self.lock.data.client.clone(),
+            self.lock.data.key.clone(),
+            self.uuid,
+            self.lock.separator.clone(),
+            WRITER_RENEW,
+            self.lock.lease,
+            interval,
+        ));
     }
 
     /// Stores the value in Redis.
@@ -27,12 +73,14 @@ where
         T: Serialize,
     {
         let script = redis::Script::new(STORE_SCRIPT);
-        let result: i8 = script
-            .arg(&self.lock.data.key)
-            .arg(self.uuid)
-            .arg(serde_json::to_string(&value).expect("Failed to serialize value"))
-            .invoke(&mut self.conn)
-            .expect("Failed to store value. You should not see this!");
+        let result: i8 = instrumented("store", &self.lock.data.key, || {
+            script
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(self.lock.data.encode_value(&value))
+                .arg(&self.lock.separator)
+                .invoke(&mut self.conn)
+        })?;
         if result == 0 {
             return Err(RwLockError::LockExpired(self.uuid));
         }
@@ -43,24 +91,23 @@ where
     /// Loads the value from Redis.
     /// This function blocks until the value is loaded.
     /// Shadows the load operation of the guarded value.
-    pub fn acquire(&mut self) -> &T {
-        self.lock.data.cache = self.try_get();
-        self.lock.data.cache.as_ref().unwrap()
+    ///
+    /// Returns an [Error], if the value cannot be loaded from Redis.
+    pub fn acquire(&mut self) -> Result<&T, Error> {
+        self.lock.data.cache = self.try_get()?;
+        Ok(self.lock.data.cache.as_ref().unwrap())
     }
 
-    fn try_get(&mut self) -> Option<T> {
+    fn try_get(&mut self) -> Result<Option<T>, Error> {
         let script = redis::Script::new(LOAD_SCRIPT);
-        let result: Option<String> = script
-            .arg(&self.lock.data.key)
-            .arg(self.uuid)
-            .invoke(&mut self.conn)
-            .expect("Failed to load value. You should not see this!");
-        let result = result?;
-
-        if result == "nil" {
-            return None;
-        }
-        Some(serde_json::from_str(&result).expect("Failed to deserialize value"))
+        let result: Option<Vec<u8>> = instrumented("acquire", &self.lock.data.key, || {
+            script
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(&self.lock.separator)
+                .invoke(&mut self.conn)
+        })?;
+        Ok(result.map(|v| self.lock.data.decode_value(&v)))
     }
 }
 
@@ -80,11 +127,14 @@ impl<'a, T> DerefMut for RwLockWriteGuard<'a, T> {
 
 impl<'a, T> Drop for RwLockWriteGuard<'a, T> {
     fn drop(&mut self) {
-        let mut conn = self.client.get_connection().unwrap();
-        let _: () = redis::Script::new(WRITER_LOCK_DROP)
-            .arg(&self.lock.data.key)
-            .arg(self.uuid)
-            .invoke(&mut conn)
-            .unwrap();
+        self.renewer.take();
+        // errors are ignored, because drop cannot return them. The lock expires after its lease instead.
+        let _ = instrumented("unlock", &self.lock.data.key, || {
+            redis::Script::new(WRITER_LOCK_DROP)
+                .arg(&self.lock.data.key)
+                .arg(self.uuid)
+                .arg(&self.lock.separator)
+                .invoke::<()>(&mut self.conn)
+        });
     }
 }
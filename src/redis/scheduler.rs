@@ -0,0 +1,120 @@
+//! # Scheduler
+//! This module contains the scheduler type, which lets one instance run a job per interval.
+use crate::redis::{instrumented, Connector};
+use std::time::Duration;
+
+/// A distributed scheduler, which fires once per interval across all instances.
+///
+/// Every instance calls [Scheduler::tick] regularly, e.g. in its main loop.
+/// The first instance of an interval wins the tick by setting the key with SET NX and the interval as expiry.
+/// All other instances lose until the key expires, so a job runs once per interval, regardless of the number of replicas.
+///
+/// The intervals start with the winning tick, so they drift by the time between the expiry and the next call of tick.
+/// If the winner crashes while running the job, the job is not run again before the next interval.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::Scheduler;
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut a = Scheduler::new("test_scheduler_example", Duration::from_millis(100), client.clone());
+/// let mut b = Scheduler::new("test_scheduler_example", Duration::from_millis(100), client);
+/// a.reset();
+///
+/// assert!(a.tick());
+/// assert!(!b.tick());
+/// std::thread::sleep(Duration::from_millis(150));
+/// assert!(b.tick());
+/// ```
+pub struct Scheduler {
+    key: String,
+    client: Connector,
+    interval: Duration,
+}
+
+impl Scheduler {
+    /// Creates a new Scheduler with the given interval.
+    /// The interval is rounded down to milliseconds, but it is at least one millisecond.
+    pub fn new(key: &str, interval: Duration, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+            interval,
+        }
+    }
+
+    /// Returns true, if this instance won the current interval and should run the job now.
+    /// Returns false, if another instance won it already.
+    pub fn tick(&mut self) -> bool {
+        let mut conn = self.client.get_connection().unwrap();
+        let millis = (self.interval.as_millis() as u64).max(1);
+        let res: Option<String> = instrumented("tick", &self.key, || {
+            redis::cmd("SET")
+                .arg(&self.key)
+                .arg(1)
+                .arg("NX")
+                .arg("PX")
+                .arg(millis)
+                .query(&mut conn)
+        })
+        .expect("Failed to tick");
+        res.is_some()
+    }
+
+    /// Returns the time until the current interval ends and the next tick can be won.
+    /// Returns zero, if the next tick can be won now.
+    pub fn next_tick_in(&self) -> Duration {
+        let mut conn = self.client.get_connection().unwrap();
+        let millis: i64 = instrumented("next_tick_in", &self.key, || {
+            redis::Cmd::pttl(&self.key).query(&mut conn)
+        })
+        .expect("Failed to get next tick");
+        // PTTL returns a negative value, if there is no running interval
+        u64::try_from(millis)
+            .map(Duration::from_millis)
+            .unwrap_or_default()
+    }
+
+    /// Ends the current interval, so the next tick can be won immediately.
+    pub fn reset(&mut self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("reset", &self.key, || {
+            redis::Cmd::del(&self.key).query::<()>(&mut conn)
+        })
+        .expect("Failed to reset scheduler");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn test_scheduler() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut scheduler =
+            Scheduler::new("test_scheduler", Duration::from_secs(10), client.clone());
+        scheduler.reset();
+        assert_eq!(scheduler.next_tick_in(), Duration::ZERO);
+
+        let runs = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let mut scheduler =
+                        Scheduler::new("test_scheduler", Duration::from_secs(10), client.clone());
+                    if scheduler.tick() {
+                        runs.fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+        // only one of the replicas runs the job in this interval
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+        assert!(scheduler.next_tick_in() > Duration::from_secs(9));
+        scheduler.reset();
+    }
+}
@@ -0,0 +1,85 @@
+//! # Snapshot
+//! This module contains a consistent read of multiple keys.
+use crate::redis::compression::try_decode;
+use crate::redis::{instrumented, Connector};
+use std::collections::HashMap;
+
+/// Loads the values of all keys at the same moment with a single MGET.
+///
+/// Redis executes every command atomically, so no other instance can update one of the keys
+/// in between, like it could between two separate loads of the types.
+/// Use it, if an invariant spans multiple keys.
+///
+/// The values are decoded from JSON like the default encoding of [Generic](crate::redis::Generic),
+/// so different types can be read at once. Missing keys are not part of the map.
+/// A value, which is no JSON, e.g. stored by [DString::with_plain](crate::redis::DString::with_plain)
+/// or [Generic::with_messagepack](crate::redis::Generic::with_messagepack), is None.
+/// The primary is used, even if a replica is configured in the [Connector].
+///
+/// # Example
+/// ```
+/// use dtypes::redis::{snapshot, Di32};
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let from = Di32::with_value(10, "test_snapshot_example_from", client.clone());
+/// let to = Di32::with_value(5, "test_snapshot_example_to", client.clone());
+///
+/// let values = snapshot(client, &["test_snapshot_example_from", "test_snapshot_example_to"]);
+/// assert_eq!(values["test_snapshot_example_from"], Some(10.into()));
+/// assert_eq!(values["test_snapshot_example_to"], Some(5.into()));
+/// ```
+pub fn snapshot(
+    client: impl Into<Connector>,
+    keys: &[&str],
+) -> HashMap<String, Option<serde_json::Value>> {
+    if keys.is_empty() {
+        return HashMap::new();
+    }
+
+    let client = client.into();
+    let mut conn = client.get_connection().unwrap();
+    let values: Vec<Option<Vec<u8>>> = instrumented("snapshot", keys[0], || {
+        redis::cmd("MGET").arg(keys).query(&mut conn)
+    })
+    .expect("Failed to load values");
+
+    keys.iter()
+        .zip(values)
+        .filter_map(|(key, value)| value.map(|v| (key.to_string(), try_decode(&v))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::{DString, Di32};
+
+    #[test]
+    fn test_snapshot() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let _i32 = Di32::with_value(1, "test_snapshot_i32", client.clone());
+        let _string = DString::with_value("a".to_string(), "test_snapshot_string", client.clone());
+        let mut plain = DString::with_plain("test_snapshot_plain", client.clone());
+        plain.store("not json".to_string());
+        let mut conn = client.get_connection().unwrap();
+        redis::Cmd::del("test_snapshot_missing")
+            .query::<()>(&mut conn)
+            .unwrap();
+
+        let values = snapshot(
+            client.clone(),
+            &[
+                "test_snapshot_i32",
+                "test_snapshot_string",
+                "test_snapshot_plain",
+                "test_snapshot_missing",
+            ],
+        );
+        assert_eq!(values.len(), 3);
+        assert_eq!(values["test_snapshot_i32"], Some(1.into()));
+        assert_eq!(values["test_snapshot_string"], Some("a".into()));
+        // a value, which is no JSON, does not fail the whole snapshot
+        assert_eq!(values["test_snapshot_plain"], None);
+        assert!(snapshot(client, &[]).is_empty());
+    }
+}
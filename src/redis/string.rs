@@ -1,13 +1,227 @@
 //! # String Type
 //! This module contains the string type.
-use crate::redis::Generic;
+use crate::redis::compression::Encoding;
+use crate::redis::{instrumented, Connector, Generic};
+use redis::RedisResult;
 use std::ops::{Add, AddAssign};
 
+/// The replace script.
+/// It is used to replace all occurrences of a substring in one atomic step.
+/// The substring is escaped, so it is not interpreted as Lua pattern.
+/// Returns the number of replacements and the new stored value.
+///
+/// Takes 4 Arguments:
+/// 1. The key of the string,
+/// 2. The substring to replace,
+/// 3. The replacement,
+/// 4. "json", if the string is stored as JSON, otherwise it is stored as it is.
+const REPLACE_SCRIPT: &str = r#"
+local value = redis.call("GET", ARGV[1])
+if value == false then
+    return {0, false}
+end
+if ARGV[4] == "json" then
+    value = cjson.decode(value)
+end
+local from = string.gsub(ARGV[2], "%p", "%%%0")
+local to = string.gsub(ARGV[3], "%%", "%%%%")
+local replaced, count = string.gsub(value, from, to)
+if count == 0 then
+    return {0, false}
+end
+if ARGV[4] == "json" then
+    replaced = cjson.encode(replaced)
+end
+redis.call("SET", ARGV[1], replaced)
+return {count, replaced}"#;
+
 pub type TString = Generic<String>;
 
+impl TString {
+    /// The with_plain method creates a new instance of the type like [Generic::new],
+    /// which stores the string as it is instead of JSON.
+    ///
+    /// The byte offsets of [TString::get_range] and [TString::set_range] are only meaningful in this mode,
+    /// because a JSON string starts with a quote and escapes some characters.
+    /// A [Mutex](crate::redis::Mutex) or [RwLock](crate::redis::RwLock) around the string keeps storing it as it is.
+    pub fn with_plain(field_name: impl Into<String>, client: impl Into<Connector>) -> Self {
+        let mut new_type = Self::new(field_name, client);
+        new_type.encoding = Encoding::Plain;
+        new_type
+    }
+
+    /// Returns the substring between the byte offsets with GETRANGE, without loading the whole string.
+    /// Negative offsets count from the end like in Redis and both offsets are inclusive.
+    /// If a replica is configured in the [Connector], it is read from the replica.
+    ///
+    /// The offsets refer to the stored bytes, so use [TString::with_plain] to store the string as it is.
+    /// Invalid UTF-8 at the borders of the range is replaced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::DString;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut s = DString::with_plain("test_string_range", client);
+    /// s.store("Hello World".to_string());
+    /// assert_eq!(s.get_range(0, 4), "Hello");
+    /// assert_eq!(s.get_range(-5, -1), "World");
+    /// s.set_range(6, "Redis");
+    /// assert_eq!(s, "Hello Redis");
+    /// ```
+    pub fn get_range(&self, start: isize, end: isize) -> String {
+        let mut conn = self.get_read_conn();
+        let res: RedisResult<Vec<u8>> = instrumented("get_range", &self.key, || {
+            redis::Cmd::getrange(&self.key, start, end).query(&mut conn)
+        });
+        String::from_utf8_lossy(&res.expect("Failed to get range")).into_owned()
+    }
+
+    /// Overwrites the stored bytes from the offset with the replacement with SETRANGE.
+    /// If the string is shorter than the offset, it is padded with zero bytes like in Redis.
+    /// The cache is updated with the patched string in the same round trip.
+    ///
+    /// The offset refers to the stored bytes, so use [TString::with_plain] to store the string as it is.
+    pub fn set_range(&mut self, offset: usize, replacement: &str) {
+        let mut conn = self.get_conn();
+        let res: RedisResult<(Vec<u8>,)> = instrumented("set_range", &self.key, || {
+            redis::pipe()
+                .atomic()
+                .setrange(&self.key, offset as isize, replacement)
+                .ignore()
+                .get(&self.key)
+                .query(&mut conn)
+        });
+        let (value,) = res.expect("Failed to set range");
+        self.cache = Some(self.decode_value(&value));
+    }
+
+    /// Appends the string in Redis with APPEND and returns the new length in bytes.
+    /// In contrast to `+=`, the string is not transferred and concurrent appends are not lost.
+    ///
+    /// The cache is extended, if it is in sync with Redis. Otherwise it is cleared,
+    /// because another instance changed the string, so use [Generic::acquire] to load it again.
+    ///
+    /// Only strings stored with [TString::with_plain] can be appended, because appending to JSON breaks it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is not stored with [TString::with_plain].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::DString;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut s = DString::with_plain("test_string_append", client);
+    /// s.store("Hello".to_string());
+    /// assert_eq!(s.append(" World"), 11);
+    /// assert_eq!(s.strlen(), 11);
+    /// assert_eq!(s, "Hello World");
+    /// ```
+    pub fn append(&mut self, s: &str) -> usize {
+        assert!(
+            self.encoding == Encoding::Plain,
+            "Only strings stored with with_plain can be appended"
+        );
+        let mut conn = self.get_conn();
+        let res: RedisResult<usize> = instrumented("append", &self.key, || {
+            redis::Cmd::append(&self.key, s).query(&mut conn)
+        });
+        let len = res.expect("Failed to append value");
+
+        self.cache = match self.cache.take() {
+            Some(mut value) if value.len() + s.len() == len => {
+                value.push_str(s);
+                Some(value)
+            }
+            None if s.len() == len => Some(s.to_string()),
+            _ => None,
+        };
+        len
+    }
+
+    /// Replaces all occurrences of `from` with `to` in Redis and returns the number of replacements.
+    /// The string is read, replaced and written back in one script, so concurrent writes are not lost
+    /// like with a replace on the cache.
+    ///
+    /// The cache is updated with the new string, if something was replaced.
+    /// Does nothing, if there is no string stored or `from` is empty.
+    ///
+    /// Strings stored with [Generic::with_compression] cannot be replaced, because they are not readable by the script.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stored value is compressed or not a string.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dtypes::redis::DString;
+    ///
+    /// let client = redis::Client::open("redis://localhost:6379").unwrap();
+    /// let mut s = DString::with_value("a-b-c".to_string(), "test_string_replace", client);
+    /// assert_eq!(s.replace("-", "+"), 2);
+    /// assert_eq!(s, "a+b+c");
+    /// ```
+    pub fn replace(&mut self, from: &str, to: &str) -> usize {
+        if from.is_empty() {
+            return 0;
+        }
+
+        let format = if self.encoding == Encoding::Plain {
+            "plain"
+        } else {
+            "json"
+        };
+        let mut conn = self.get_conn();
+        let res: RedisResult<(usize, Option<Vec<u8>>)> = instrumented("replace", &self.key, || {
+            redis::Script::new(REPLACE_SCRIPT)
+                .arg(&self.key)
+                .arg(from)
+                .arg(to)
+                .arg(format)
+                .invoke(&mut conn)
+        });
+        let (count, value) = res.expect("Failed to replace value");
+        if let Some(value) = value {
+            self.cache = Some(self.decode_value(&value));
+        }
+        count
+    }
+
+    /// Returns the length of the stored string in bytes with STRLEN, without loading it.
+    /// If a replica is configured in the [Connector], it is read from the replica.
+    ///
+    /// For strings stored as JSON, the length includes the quotes and escapes.
+    /// Use [TString::with_plain] to get the length of the string itself.
+    /// The length of the cached string is returned by `len` like for [String].
+    pub fn strlen(&self) -> usize {
+        let mut conn = self.get_read_conn();
+        let res: RedisResult<usize> = instrumented("strlen", &self.key, || {
+            redis::Cmd::strlen(&self.key).query(&mut conn)
+        });
+        res.expect("Failed to get length")
+    }
+}
+
 impl PartialEq<&str> for TString {
     fn eq(&self, other: &&str) -> bool {
-        self.cache.as_ref().map_or(false, |v| v == *other)
+        self.cache.as_ref().is_some_and(|v| v == *other)
+    }
+}
+
+impl PartialEq<TString> for &str {
+    fn eq(&self, other: &TString) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<TString> for String {
+    fn eq(&self, other: &TString) -> bool {
+        other == self
     }
 }
 
@@ -54,11 +268,88 @@ mod tests {
         assert_eq!(s3, "Togethertest");
     }
 
+    #[test]
+    fn test_range() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1 = TString::with_plain("test_string_range", client.clone());
+        s1.store("Hello \"World\"".to_string());
+        assert_eq!(s1.get_range(7, 11), "World");
+        s1.set_range(0, "Jello");
+        assert_eq!(s1, "Jello \"World\"");
+        let mut s2 = TString::with_plain("test_string_range", client);
+        assert_eq!(s2.acquire(), "Jello \"World\"");
+    }
+
+    #[test]
+    fn test_plain_lock() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let s1 = TString::with_plain("test_string_plain_lock", client.clone());
+        let mut lock = crate::redis::Mutex::new(s1);
+        {
+            let mut guard = lock.lock().unwrap();
+            guard.store("Hello".to_string()).unwrap();
+            assert_eq!(guard.acquire().unwrap(), "Hello");
+        }
+        let mut s2 = TString::with_plain("test_string_plain_lock", client);
+        assert_eq!(s2.get_range(0, -1), "Hello");
+        assert_eq!(s2.acquire(), "Hello");
+    }
+
+    #[test]
+    fn test_append() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1 = TString::with_plain("test_string_append", client.clone());
+        let mut s2 = TString::with_plain("test_string_append", client);
+        s1.store("a".to_string());
+        s2.acquire();
+        assert_eq!(s1.append("b"), 2);
+        assert_eq!(s1, "ab");
+        // the cache of s2 is outdated
+        assert_eq!(s2.append("c"), 3);
+        assert_eq!(s2.cached(), None);
+        assert_eq!(s2.acquire(), "abc");
+        assert_eq!(s2.strlen(), 3);
+        assert_eq!(s2.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only strings stored with with_plain can be appended")]
+    fn test_append_json() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1 = TString::with_value("a".to_string(), "test_string_append_json", client);
+        s1.append("b");
+    }
+
+    #[test]
+    fn test_replace() {
+        let client = redis::Client::open("redis://localhost/").unwrap();
+        let mut s1 = TString::with_value(
+            "a.b \"c\" 100%".to_string(),
+            "test_string_replace",
+            client.clone(),
+        );
+        assert_eq!(s1.replace(".", "%1"), 1);
+        assert_eq!(s1.replace("\"c\"", "/"), 1);
+        assert_eq!(s1.replace("x", "y"), 0);
+        assert_eq!(s1, "a%1b / 100%");
+        let mut s2 = TString::with_load("test_string_replace", client.clone());
+        assert_eq!(s2.acquire(), "a%1b / 100%");
+
+        let mut s3 = TString::with_plain("test_string_replace_plain", client);
+        s3.store("aaa".to_string());
+        assert_eq!(s3.replace("a", "bb"), 3);
+        assert_eq!(s3, "bbbbbb");
+        assert_eq!(s3.strlen(), 6);
+    }
+
     #[test]
     fn test_partialeq() {
         let client = redis::Client::open("redis://localhost/").unwrap();
         let s1 = TString::with_value("Hello".to_string(), "s1", client.clone());
         assert_eq!(s1, "Hello");
         assert_ne!(s1, "World");
+        assert_eq!("Hello", s1);
+        assert_eq!(s1, "Hello".to_string());
+        assert_eq!("Hello".to_string(), s1);
     }
 }
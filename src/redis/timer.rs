@@ -0,0 +1,133 @@
+//! # Timer
+//! This module contains the timer type, which measures the time with the clock of Redis.
+use crate::redis::{instrumented, Connector};
+use std::time::Duration;
+
+/// The start script.
+/// It is used to store the current time of the redis server in microseconds.
+///
+/// Takes 1 Argument:
+/// 1. The key of the timer.
+const START_SCRIPT: &str = r#"
+local time = redis.call("TIME")
+redis.call("SET", ARGV[1], string.format("%d", time[1] * 1000000 + time[2]))
+return 1"#;
+
+/// The elapsed script.
+/// It is used to compute the elapsed microseconds since the start with the time of the redis server.
+/// Returns -1, if the timer is not started.
+///
+/// Takes 1 Argument:
+/// 1. The key of the timer.
+const ELAPSED_SCRIPT: &str = r#"
+local start = redis.call("GET", ARGV[1])
+if not start then
+    return -1
+end
+local time = redis.call("TIME")
+return time[1] * 1000000 + time[2] - tonumber(start)"#;
+
+/// A stopwatch, which stores its start time in Redis.
+///
+/// The start and the elapsed time are taken from the clock of the redis server,
+/// so every instance measures the same time, even if the clocks of the instances are skewed.
+/// This can be used to check how long a distributed job is running, regardless of the instance, which started it.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::Timer;
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut timer = Timer::new("test_timer_example", client.clone());
+/// timer.start();
+/// std::thread::sleep(Duration::from_millis(10));
+///
+/// let other = Timer::new("test_timer_example", client);
+/// assert!(other.elapsed() >= Duration::from_millis(10));
+/// ```
+pub struct Timer {
+    key: String,
+    client: Connector,
+}
+
+impl Timer {
+    /// Creates a new Timer.
+    /// A start time, which is already stored in redis, is kept.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+        }
+    }
+
+    /// Starts the timer with the current time of the redis server.
+    /// A running timer is restarted.
+    pub fn start(&mut self) {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("start", &self.key, || {
+            redis::Script::new(START_SCRIPT)
+                .arg(&self.key)
+                .invoke::<()>(&mut conn)
+        })
+        .expect("Failed to start timer");
+    }
+
+    /// Returns the elapsed time since the start.
+    /// Returns zero, if the timer is not started.
+    pub fn elapsed(&self) -> Duration {
+        let mut conn = self.client.get_connection().unwrap();
+        let micros: i64 = instrumented("elapsed", &self.key, || {
+            redis::Script::new(ELAPSED_SCRIPT)
+                .arg(&self.key)
+                .invoke(&mut conn)
+        })
+        .expect("Failed to get elapsed time");
+        u64::try_from(micros)
+            .map(Duration::from_micros)
+            .unwrap_or_default()
+    }
+
+    /// Returns true, if the timer is started.
+    pub fn is_running(&self) -> bool {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("is_running", &self.key, || {
+            redis::Cmd::exists(&self.key).query(&mut conn)
+        })
+        .expect("Failed to check timer")
+    }
+
+    /// Stops the timer and returns the elapsed time since the start.
+    /// Returns zero, if the timer was not started.
+    pub fn stop(&mut self) -> Duration {
+        let elapsed = self.elapsed();
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("stop", &self.key, || {
+            redis::Cmd::del(&self.key).query::<()>(&mut conn)
+        })
+        .expect("Failed to stop timer");
+        elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut timer = Timer::new("test_timer", client);
+        timer.stop();
+        assert!(!timer.is_running());
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+
+        timer.start();
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(timer.is_running());
+        let elapsed = timer.stop();
+        assert!(elapsed >= Duration::from_millis(100), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(1), "{:?}", elapsed);
+        assert!(!timer.is_running());
+    }
+}
@@ -0,0 +1,167 @@
+//! # TTL Queue
+//! This module contains the queue type with an expiry per item.
+use crate::redis::{instrumented, Connector};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+/// The push script.
+/// It is used to add an item with the time in milliseconds as prefix, when it expires.
+/// The time of the redis server is used, so all instances share the same clock.
+/// Items without a ttl get 0 as prefix and never expire.
+///
+/// Takes 3 Arguments:
+/// 1. The key of the queue,
+/// 2. The ttl in milliseconds or -1 for no ttl,
+/// 3. The value to push.
+const PUSH_SCRIPT: &str = r#"
+local expires = 0
+local ttl = tonumber(ARGV[2])
+if ttl >= 0 then
+    local time = redis.call("TIME")
+    expires = time[1] * 1000 + math.floor(time[2] / 1000) + ttl
+end
+redis.call("RPUSH", ARGV[1], string.format("%d", expires) .. ":" .. ARGV[3])
+return 1"#;
+
+/// The pop script.
+/// It is used to remove the oldest item, which is not expired yet.
+/// All expired items in front of it are removed as well.
+///
+/// Takes 1 Argument:
+/// 1. The key of the queue.
+const POP_SCRIPT: &str = r#"
+local time = redis.call("TIME")
+local now = time[1] * 1000 + math.floor(time[2] / 1000)
+while true do
+    local item = redis.call("LPOP", ARGV[1])
+    if not item then
+        return nil
+    end
+    local sep = string.find(item, ":", 1, true)
+    local expires = tonumber(string.sub(item, 1, sep - 1))
+    if expires == 0 or expires > now then
+        return string.sub(item, sep + 1)
+    end
+end"#;
+
+/// A FIFO queue, where every item can have its own time to live.
+///
+/// The items are stored in a list in Redis with the time as prefix, when they expire.
+/// Expired items are skipped and removed on [TtlQueue::pop], so stale jobs are dropped
+/// instead of being handled by a consumer. Multiple instances can push and pop on the same key.
+///
+/// # Example
+/// ```
+/// use dtypes::redis::TtlQueue;
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://localhost:6379").unwrap();
+/// let mut queue = TtlQueue::new("test_ttl_queue_example", client);
+/// queue.clear();
+/// queue.push_with_ttl(&1, Duration::from_millis(0));
+/// queue.push(&2);
+/// assert_eq!(queue.len(), 2);
+/// assert_eq!(queue.pop(), Some(2));
+/// assert_eq!(queue.pop(), None);
+/// ```
+pub struct TtlQueue<T> {
+    key: String,
+    client: Connector,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> TtlQueue<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new TtlQueue.
+    /// Already stored items in redis are kept.
+    pub fn new(key: &str, client: impl Into<Connector>) -> Self {
+        Self {
+            key: key.to_string(),
+            client: client.into(),
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Adds the item to the back of the queue, which never expires.
+    pub fn push(&mut self, item: &T) {
+        self.push_inner("push", item, -1);
+    }
+
+    /// Adds the item to the back of the queue, which is skipped on pop after the ttl.
+    pub fn push_with_ttl(&mut self, item: &T, ttl: Duration) {
+        self.push_inner("push_with_ttl", item, ttl.as_millis() as i64);
+    }
+
+    fn push_inner(&mut self, op: &str, item: &T, ttl: i64) {
+        let mut conn = self.client.get_connection().unwrap();
+        let item = serde_json::to_string(item).expect("Failed to serialize value");
+        instrumented(op, &self.key, || {
+            redis::Script::new(PUSH_SCRIPT)
+                .arg(&self.key)
+                .arg(ttl)
+                .arg(item)
+                .invoke::<()>(&mut conn)
+        })
+        .expect("Failed to push value");
+    }
+
+    /// Removes and returns the oldest item, which is not expired.
+    /// Returns None, if there is no such item.
+    pub fn pop(&mut self) -> Option<T> {
+        let mut conn = self.client.get_connection().unwrap();
+        let val: Option<String> = instrumented("pop", &self.key, || {
+            redis::Script::new(POP_SCRIPT)
+                .arg(&self.key)
+                .invoke(&mut conn)
+        })
+        .expect("Failed to pop value");
+        val.map(|v| serde_json::from_str(&v).expect("Failed to deserialize value"))
+    }
+
+    /// Returns the number of items in the queue.
+    /// Expired items are counted until they are removed by [TtlQueue::pop].
+    pub fn len(&self) -> usize {
+        let mut conn = self.client.get_connection().unwrap();
+        instrumented("len", &self.key, || {
+            redis::Cmd::llen(&self.key).query(&mut conn)
+        })
+        .unwrap()
+    }
+
+    /// Returns true if the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all items from the queue
+    pub fn clear(&self) {
+        let mut conn = self.client.get_connection().unwrap();
+        let cmd = redis::Cmd::del(&self.key);
+        instrumented("clear", &self.key, || cmd.query::<()>(&mut conn))
+            .expect("Failed to clear queue");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ttl_queue() {
+        let client = redis::Client::open("redis://localhost:6379").unwrap();
+        let mut queue = TtlQueue::new("test_ttl_queue", client);
+        queue.clear();
+        queue.push_with_ttl(&"stale".to_string(), Duration::from_millis(100));
+        queue.push(&"forever".to_string());
+        queue.push_with_ttl(&"fresh".to_string(), Duration::from_secs(60));
+        assert_eq!(queue.len(), 3);
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(queue.pop(), Some("forever".to_string()));
+        assert_eq!(queue.pop(), Some("fresh".to_string()));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+}